@@ -0,0 +1,350 @@
+use crate::calendar::TradingCalendar;
+use crate::interval::Interval;
+use crate::series::PriceSeries;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Policies aligning the bars of assets trading on different calendars.
+///
+/// Statistics are computed on closes sampled on the aligned bars, so each
+/// return spans every bar skipped before it: skipping a crypto weekend rolls
+/// its returns into the following session rather than dropping them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Alignment {
+    /// Keeps the bars every series has.
+    #[default]
+    Intersect,
+    /// Keeps the bars any series has, carrying the last close of the series without one.
+    ///
+    /// Exchange-traded assets get flat prices, hence zero returns, on weekends and holidays.
+    ForwardFill,
+    /// Keeps the sessions of the exchange-traded series, taking the last close of the others.
+    ///
+    /// Crypto returns of weekends and holidays are aggregated into the next session.
+    WeekendRoll,
+    /// Samples every series on the sessions of the common calendar, taking their last close.
+    ///
+    /// Unlike `WeekendRoll`, sessions missing from an exchange-traded series are filled too.
+    /// Hourly bars are sampled on the trading hours of the exchange-traded series.
+    Resample,
+}
+
+impl Alignment {
+    /// Creates an `Alignment` enum from a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `alignment` - A string slice representing the alignment name.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Alignment>` - Representing the alignment if valid, `None` if invalid.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(alignment: &str) -> Option<Alignment> {
+        match alignment.to_lowercase().as_str() {
+            "intersect" | "inner" => Some(Alignment::Intersect),
+            "forward_fill" | "ffill" | "union" => Some(Alignment::ForwardFill),
+            "weekend_roll" | "weekend" | "monday" => Some(Alignment::WeekendRoll),
+            "resample" => Some(Alignment::Resample),
+            _ => None,
+        }
+    }
+
+    /// Returns the calendar of the aligned series, which sets their annualization factor.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendars` - The trading calendar of each series.
+    ///
+    /// # Returns
+    ///
+    /// * `TradingCalendar` - The union calendar when forward-filling, the common calendar otherwise.
+    pub fn calendar(&self, calendars: &[TradingCalendar]) -> TradingCalendar {
+        let merge = match self {
+            Alignment::ForwardFill => TradingCalendar::union,
+            _ => TradingCalendar::common,
+        };
+
+        calendars
+            .iter()
+            .copied()
+            .reduce(merge)
+            .unwrap_or(TradingCalendar::Equity)
+    }
+
+    /// Aligns price series on a common set of bars.
+    ///
+    /// Bars before the first bar of any series are dropped, so every kept
+    /// bar has a close for every series.
+    ///
+    /// # Arguments
+    ///
+    /// * `series` - Bars of each token keyed by bar start.
+    /// * `calendars` - The trading calendar of each token.
+    /// * `interval` - The bar interval, only hourly and daily bars are checked against sessions.
+    ///
+    /// # Returns
+    ///
+    /// * `(Vec<NaiveDateTime>, Vec<Vec<f64>>)` - The aligned bars and the closing prices of each token on them, in chronological order.
+    pub fn align(
+        &self,
+        series: &[PriceSeries],
+        calendars: &[TradingCalendar],
+        interval: Interval,
+    ) -> (Vec<NaiveDateTime>, Vec<Vec<f64>>) {
+        if series.is_empty() {
+            return (Vec::new(), Vec::new());
+        }
+
+        let common = self.calendar(calendars);
+        let is_session = |date: &NaiveDateTime| match interval {
+            Interval::Hourly | Interval::Daily => common.is_session(date.date()),
+            Interval::Weekly | Interval::Monthly => true,
+        };
+
+        // Series trading on the common calendar set the sessions
+        let restrictive = || {
+            series
+                .iter()
+                .zip(calendars)
+                .filter(|(_, calendar)| **calendar == common)
+                .map(|(data, _)| data)
+        };
+
+        // Bars are keyed in chronological order, which returns rely on
+        let dates: Vec<NaiveDateTime> = match self {
+            Alignment::Intersect => Self::intersection(series.iter()),
+            Alignment::ForwardFill => Self::union(series.iter()),
+            Alignment::WeekendRoll => Self::intersection(restrictive())
+                .into_iter()
+                .filter(is_session)
+                .collect(),
+            Alignment::Resample => {
+                // Session dates do not bound trading hours, hourly bars are the exchange's own
+                let bars = match interval {
+                    Interval::Hourly => Self::union(restrictive()),
+                    _ => Self::union(series.iter()),
+                };
+
+                bars.into_iter().filter(is_session).collect()
+            }
+        };
+
+        // A bar is only kept once every series has started
+        let first = series
+            .iter()
+            .filter_map(|data| data.bars.keys().next())
+            .max()
+            .copied();
+        let dates: Vec<NaiveDateTime> = match first {
+            Some(first) if series.iter().all(|data| !data.is_empty()) => {
+                dates.into_iter().filter(|date| *date >= first).collect()
+            }
+            _ => Vec::new(),
+        };
+
+        let aligned = series
+            .iter()
+            .map(|data| {
+                dates
+                    .iter()
+                    .map(|date| {
+                        data.bars
+                            .range(..=date)
+                            .next_back()
+                            .map(|(_, bar)| bar.close)
+                            .expect("every series starts before the aligned bars")
+                    })
+                    .collect()
+            })
+            .collect();
+
+        (dates, aligned)
+    }
+
+    /// Returns the bar starts every series has.
+    fn intersection<'a, I: Iterator<Item = &'a PriceSeries>>(mut series: I) -> Vec<NaiveDateTime> {
+        let first = match series.next() {
+            Some(first) => first,
+            None => return Vec::new(),
+        };
+        let others: Vec<&PriceSeries> = series.collect();
+
+        first
+            .bars
+            .keys()
+            .filter(|date| others.iter().all(|data| data.bars.contains_key(date)))
+            .copied()
+            .collect()
+    }
+
+    /// Returns the bar starts any series has.
+    fn union<'a, I: Iterator<Item = &'a PriceSeries>>(series: I) -> Vec<NaiveDateTime> {
+        series
+            .flat_map(|data| data.bars.keys().copied())
+            .collect::<BTreeSet<NaiveDateTime>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Alignment;
+    use crate::calendar::TradingCalendar;
+    use crate::interval::Interval;
+    use crate::series::{Bar, PriceSeries};
+    use crate::token::Token;
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    const CALENDARS: [TradingCalendar; 2] = [TradingCalendar::Crypto, TradingCalendar::Equity];
+
+    fn at(month: u32, day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, month, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn series(token: &str, interval: Interval, closes: &[(NaiveDateTime, f64)]) -> PriceSeries {
+        let raw_bars = closes
+            .iter()
+            .map(|&(date, close)| {
+                Some(Bar {
+                    date,
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: None,
+                    adjclose: None,
+                })
+            })
+            .collect();
+
+        PriceSeries::from_raw_bars(&Token::from_str(token).unwrap(), interval, raw_bars)
+    }
+
+    /// Daily bars around Good Friday and the Easter weekend, across the end of March.
+    ///
+    /// The crypto series misses April 1st and the equity series April 2nd.
+    fn easter_series() -> [PriceSeries; 2] {
+        let crypto = [
+            (at(3, 27, 0), 0.5),
+            (at(3, 28, 0), 1.0),
+            (at(3, 29, 0), 2.0),
+            (at(3, 30, 0), 3.0),
+            (at(3, 31, 0), 4.0),
+            (at(4, 2, 0), 6.0),
+            (at(4, 3, 0), 7.0),
+        ];
+        let equity = [
+            (at(3, 28, 0), 10.0),
+            (at(4, 1, 0), 11.0),
+            (at(4, 3, 0), 13.0),
+        ];
+
+        [
+            series("BTC", Interval::Daily, &crypto),
+            series("SPX", Interval::Daily, &equity),
+        ]
+    }
+
+    fn align(alignment: Alignment) -> (Vec<NaiveDateTime>, Vec<Vec<f64>>) {
+        alignment.align(&easter_series(), &CALENDARS, Interval::Daily)
+    }
+
+    #[test]
+    fn intersect_keeps_common_bars() {
+        let (dates, aligned) = align(Alignment::Intersect);
+
+        assert_eq!(dates, vec![at(3, 28, 0), at(4, 3, 0)]);
+        assert_eq!(aligned, vec![vec![1.0, 7.0], vec![10.0, 13.0]]);
+    }
+
+    #[test]
+    fn forward_fill_keeps_every_bar_once_both_series_started() {
+        let (dates, aligned) = align(Alignment::ForwardFill);
+
+        assert_eq!(
+            dates,
+            vec![
+                at(3, 28, 0),
+                at(3, 29, 0),
+                at(3, 30, 0),
+                at(3, 31, 0),
+                at(4, 1, 0),
+                at(4, 2, 0),
+                at(4, 3, 0),
+            ]
+        );
+        assert_eq!(
+            aligned,
+            vec![
+                vec![1.0, 2.0, 3.0, 4.0, 4.0, 6.0, 7.0],
+                vec![10.0, 10.0, 10.0, 10.0, 11.0, 11.0, 13.0],
+            ]
+        );
+    }
+
+    #[test]
+    fn weekend_roll_keeps_the_equity_sessions() {
+        let (dates, aligned) = align(Alignment::WeekendRoll);
+
+        // Good Friday and the weekend roll into Monday, April 1st
+        assert_eq!(dates, vec![at(3, 28, 0), at(4, 1, 0), at(4, 3, 0)]);
+        assert_eq!(aligned, vec![vec![1.0, 4.0, 7.0], vec![10.0, 11.0, 13.0]]);
+    }
+
+    #[test]
+    fn resample_fills_sessions_missing_from_the_equity_series() {
+        let (dates, aligned) = align(Alignment::Resample);
+
+        assert_eq!(
+            dates,
+            vec![at(3, 28, 0), at(4, 1, 0), at(4, 2, 0), at(4, 3, 0)]
+        );
+        assert_eq!(
+            aligned,
+            vec![vec![1.0, 4.0, 6.0, 7.0], vec![10.0, 11.0, 11.0, 13.0]]
+        );
+    }
+
+    #[test]
+    fn resample_keeps_the_trading_hours_of_hourly_equity_bars() {
+        let crypto = [
+            (at(3, 30, 14), 1.0),
+            (at(4, 1, 13), 2.0),
+            (at(4, 1, 14), 3.0),
+            (at(4, 1, 15), 4.0),
+            (at(4, 1, 16), 5.0),
+        ];
+        let equity = [(at(4, 1, 14), 10.0), (at(4, 1, 15), 11.0)];
+        let series = [
+            series("BTC", Interval::Hourly, &crypto),
+            series("SPX", Interval::Hourly, &equity),
+        ];
+
+        let (dates, aligned) = Alignment::Resample.align(&series, &CALENDARS, Interval::Hourly);
+
+        assert_eq!(dates, vec![at(4, 1, 14), at(4, 1, 15)]);
+        assert_eq!(aligned, vec![vec![3.0, 4.0], vec![10.0, 11.0]]);
+    }
+
+    #[test]
+    fn forward_fill_annualizes_on_the_union_calendar() {
+        assert_eq!(
+            Alignment::ForwardFill.calendar(&CALENDARS),
+            TradingCalendar::Crypto
+        );
+        for alignment in [
+            Alignment::Intersect,
+            Alignment::WeekendRoll,
+            Alignment::Resample,
+        ] {
+            assert_eq!(alignment.calendar(&CALENDARS), TradingCalendar::Equity);
+        }
+    }
+}
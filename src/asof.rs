@@ -0,0 +1,196 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Time of day at which daily closes of around-the-clock assets are sampled.
+///
+/// A daily crypto bar is dated by the first as-of close at or after the
+/// instant it closes, so its close never looks ahead of the session it is
+/// paired with. With `16:00 America/New_York`, the bar closing at midnight
+/// UTC (20:00 or 19:00 in New York) is dated by the next New York date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsOfClose {
+    pub time: NaiveTime,
+    pub timezone: Tz,
+}
+
+impl AsOfClose {
+    /// Creates an `AsOfClose` from a `HH:MM` time and an IANA timezone name.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - A string slice of the time of day, e.g. `16:00`.
+    /// * `timezone` - A string slice of the timezone name, e.g. `America/New_York`.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<AsOfClose>` - Representing the close if valid, `None` if invalid.
+    pub fn from_str(time: &str, timezone: &str) -> Option<AsOfClose> {
+        Some(AsOfClose {
+            time: NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()?,
+            timezone: timezone.trim().parse().ok()?,
+        })
+    }
+
+    /// Returns the instant of the as-of close on a local date.
+    ///
+    /// DST transitions are resolved deterministically: a close time repeated
+    /// when clocks fall back is taken at its first occurrence, and one skipped
+    /// when clocks spring forward moves to the first local time after the gap.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The local date.
+    ///
+    /// # Returns
+    ///
+    /// * `DateTime<Utc>` - The instant of the close.
+    pub fn instant(&self, date: NaiveDate) -> DateTime<Utc> {
+        let mut local = date.and_time(self.time);
+
+        // Gaps last at most a few hours, past them every local time exists
+        loop {
+            if let Some(instant) = self.timezone.from_local_datetime(&local).earliest() {
+                return instant.with_timezone(&Utc);
+            }
+            local += Duration::minutes(1);
+        }
+    }
+
+    /// Returns the date of the first as-of close at or after an instant.
+    ///
+    /// # Arguments
+    ///
+    /// * `close` - The instant a bar closes.
+    ///
+    /// # Returns
+    ///
+    /// * `NaiveDate` - The local date of the as-of close.
+    pub fn session_date(&self, close: DateTime<Utc>) -> NaiveDate {
+        let date = close.with_timezone(&self.timezone).date_naive();
+
+        if close <= self.instant(date) {
+            date
+        } else {
+            date + Duration::days(1)
+        }
+    }
+
+    /// Dates a daily bar opening at midnight UTC by the as-of close following it.
+    ///
+    /// # Arguments
+    ///
+    /// * `bar_start` - The UTC start of the daily bar.
+    ///
+    /// # Returns
+    ///
+    /// * `NaiveDateTime` - The start of the bar it is dated by.
+    pub fn daily_bar_start(&self, bar_start: NaiveDateTime) -> NaiveDateTime {
+        let close = bar_start.and_utc() + Duration::days(1);
+
+        self.session_date(close)
+            .and_hms_opt(0, 0, 0)
+            .expect("midnight is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AsOfClose;
+    use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn midnight(year: i32, month: u32, day: u32) -> NaiveDateTime {
+        date(year, month, day).and_hms_opt(0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn closes_follow_dst_switches() {
+        let close = AsOfClose::from_str("16:00", "America/New_York").unwrap();
+
+        assert_eq!(
+            close.instant(date(2024, 3, 9)),
+            Utc.with_ymd_and_hms(2024, 3, 9, 21, 0, 0).unwrap()
+        );
+        assert_eq!(
+            close.instant(date(2024, 3, 10)),
+            Utc.with_ymd_and_hms(2024, 3, 10, 20, 0, 0).unwrap()
+        );
+        assert_eq!(
+            close.instant(date(2024, 11, 3)),
+            Utc.with_ymd_and_hms(2024, 11, 3, 21, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn skipped_and_repeated_close_times_resolve_deterministically() {
+        // 02:30 does not exist on March 10th, the close moves to 03:00 EDT
+        let skipped = AsOfClose::from_str("02:30", "America/New_York").unwrap();
+        assert_eq!(
+            skipped.instant(date(2024, 3, 10)),
+            Utc.with_ymd_and_hms(2024, 3, 10, 7, 0, 0).unwrap()
+        );
+
+        // 01:30 happens twice on November 3rd, the first one in EDT is taken
+        let repeated = AsOfClose::from_str("01:30", "America/New_York").unwrap();
+        assert_eq!(
+            repeated.instant(date(2024, 11, 3)),
+            Utc.with_ymd_and_hms(2024, 11, 3, 5, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn crypto_bars_closing_at_midnight_utc_are_dated_by_the_next_close() {
+        let close = AsOfClose::from_str("16:00", "America/New_York").unwrap();
+
+        // Closing at 19:00 EST or 20:00 EDT, after the New York close of the same day
+        assert_eq!(
+            close.daily_bar_start(midnight(2024, 3, 8)),
+            midnight(2024, 3, 9)
+        );
+        assert_eq!(
+            close.daily_bar_start(midnight(2024, 3, 10)),
+            midnight(2024, 3, 11)
+        );
+        assert_eq!(
+            close.daily_bar_start(midnight(2024, 7, 8)),
+            midnight(2024, 7, 9)
+        );
+
+        // A 20:00 close is at or after both, so the bars keep their own date across the switch
+        let evening = AsOfClose::from_str("20:00", "America/New_York").unwrap();
+        assert_eq!(
+            evening.daily_bar_start(midnight(2024, 3, 9)),
+            midnight(2024, 3, 9)
+        );
+        assert_eq!(
+            evening.daily_bar_start(midnight(2024, 3, 10)),
+            midnight(2024, 3, 10)
+        );
+    }
+
+    #[test]
+    fn closes_ahead_of_utc_cross_midnight() {
+        // Midnight UTC is 09:00 in Tokyo on the next local date
+        let tokyo = AsOfClose::from_str("09:00", "Asia/Tokyo").unwrap();
+        assert_eq!(
+            tokyo.daily_bar_start(midnight(2024, 3, 8)),
+            midnight(2024, 3, 9)
+        );
+
+        // A close before 09:00 is already past, the bar waits for the next day's close
+        let early = AsOfClose::from_str("08:00", "Asia/Tokyo").unwrap();
+        assert_eq!(
+            early.daily_bar_start(midnight(2024, 3, 8)),
+            midnight(2024, 3, 10)
+        );
+    }
+
+    #[test]
+    fn invalid_closes_are_rejected() {
+        assert!(AsOfClose::from_str("25:00", "America/New_York").is_none());
+        assert!(AsOfClose::from_str("16:00", "Mars/Olympus").is_none());
+    }
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+/// Series on which covariance and correlation are computed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Basis {
+    /// Raw closing price levels.
+    Price,
+    /// Bar-over-bar simple returns, `p2 / p1 - 1`.
+    SimpleReturn,
+    /// Bar-over-bar log returns, `ln(p2 / p1)`.
+    #[default]
+    LogReturn,
+}
+
+impl Basis {
+    /// Creates a `Basis` enum from a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `basis` - A string slice representing the basis name.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Basis>` - Representing the basis if valid, `None` if invalid.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(basis: &str) -> Option<Basis> {
+        match basis.to_lowercase().as_str() {
+            "price" => Some(Basis::Price),
+            "simple_return" | "simple" => Some(Basis::SimpleReturn),
+            "log_return" | "log" => Some(Basis::LogReturn),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use crate::interval::Interval;
+use crate::range::DateRange;
+use crate::series::Bar;
+use crate::source::SourceKind;
+use crate::token::Token;
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Version of the cache schema, older caches are dropped and rebuilt.
+const SCHEMA_VERSION: i64 = 2;
+
+/// On-disk SQLite cache of price bars, keyed by source, symbol and interval.
+pub struct PriceCache {
+    connection: Mutex<Connection>,
+    path: PathBuf,
+    ttl: Duration,
+}
+
+/// Summary of the cached bars of one source, symbol and interval.
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntry {
+    pub source: String,
+    pub symbol: String,
+    pub interval: String,
+    pub bars: usize,
+    pub first_bar: Option<NaiveDateTime>,
+    pub last_bar: Option<NaiveDateTime>,
+    /// Range the cached bars were fetched for.
+    pub covered: DateRange,
+    pub refreshed_at: DateTime<Utc>,
+    /// Whether the TTL has elapsed since the last refresh.
+    pub stale: bool,
+}
+
+impl PriceCache {
+    /// Opens (or creates) the cache database.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Location of the SQLite file.
+    /// * `ttl` - Time a cached series is considered fresh.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PriceCache, anyhow::Error>` - The opened cache or an error.
+    pub fn open(path: &Path, ttl: Duration) -> Result<PriceCache, anyhow::Error> {
+        let connection = Connection::open(path)?;
+
+        let version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        if version != SCHEMA_VERSION {
+            if version != 0 {
+                warn!(
+                    "Price cache schema <{}> is outdated, rebuilding it as <{}>.",
+                    version, SCHEMA_VERSION
+                );
+            }
+
+            connection.execute_batch(&format!(
+                "DROP TABLE IF EXISTS bars;
+                 DROP TABLE IF EXISTS entries;
+                 PRAGMA user_version = {};",
+                SCHEMA_VERSION
+            ))?;
+        }
+
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS bars (
+                source TEXT NOT NULL,
+                symbol TEXT NOT NULL,
+                interval TEXT NOT NULL,
+                date INTEGER NOT NULL,
+                open REAL NOT NULL,
+                high REAL NOT NULL,
+                low REAL NOT NULL,
+                close REAL NOT NULL,
+                volume REAL,
+                adjclose REAL,
+                PRIMARY KEY (source, symbol, interval, date)
+            );
+            CREATE TABLE IF NOT EXISTS entries (
+                source TEXT NOT NULL,
+                symbol TEXT NOT NULL,
+                interval TEXT NOT NULL,
+                covered_from INTEGER NOT NULL,
+                covered_to INTEGER NOT NULL,
+                refreshed_at INTEGER NOT NULL,
+                PRIMARY KEY (source, symbol, interval)
+            );",
+        )?;
+
+        Ok(PriceCache {
+            connection: Mutex::new(connection),
+            path: path.to_path_buf(),
+            ttl,
+        })
+    }
+
+    /// Returns the location of the cache file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the time a cached series is considered fresh.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Runs calls on the cache on the blocking thread pool.
+    ///
+    /// SQLite calls hold the connection lock while they run, so they are kept
+    /// off the async workers serving concurrent requests, as file reads are.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - The cache.
+    /// * `call` - The calls to run.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<T, anyhow::Error>` - The result of the calls, or an error.
+    pub async fn blocking<T, F>(cache: &Arc<PriceCache>, call: F) -> Result<T, anyhow::Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(&PriceCache) -> Result<T, anyhow::Error> + Send + 'static,
+    {
+        let cache = cache.clone();
+        tokio::task::spawn_blocking(move || call(&cache)).await?
+    }
+
+    /// Returns the cache entry of a source, token and interval, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source the bars were fetched from.
+    /// * `token` - The cached token.
+    /// * `interval` - The cached bar interval.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<CacheEntry>, anyhow::Error>` - The entry, `None` if nothing is cached.
+    pub fn entry(
+        &self,
+        source: SourceKind,
+        token: &Token,
+        interval: Interval,
+    ) -> Result<Option<CacheEntry>, anyhow::Error> {
+        let mut entries = self.query_entries(Some(source), Some(token.id()), Some(interval))?;
+
+        Ok(entries.pop())
+    }
+
+    /// Returns all cache entries.
+    pub fn entries(&self) -> Result<Vec<CacheEntry>, anyhow::Error> {
+        self.query_entries(None, None, None)
+    }
+
+    /// Returns the part of a range the cache cannot serve.
+    ///
+    /// A range starting outside the covered one is fetched in full. A range
+    /// ending after it only needs the tail from the last cached bar, which is
+    /// re-fetched in case it was still forming. The tail is only skipped when
+    /// the entry is fresh and was covered up to its refresh, so an old range
+    /// cached by an explicit request never hides the bars formed since.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - The cache entry, `None` if nothing is cached.
+    /// * `range` - The requested range.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<DateRange>` - The range to fetch, `None` if the cache can serve the request.
+    pub fn missing_range(
+        &self,
+        entry: Option<&CacheEntry>,
+        range: &DateRange,
+    ) -> Option<DateRange> {
+        let entry = match entry {
+            Some(entry) => entry,
+            None => return Some(*range),
+        };
+
+        if range.start < entry.covered.start || range.start > entry.covered.end {
+            return Some(*range);
+        }
+
+        if range.end <= entry.covered.end {
+            return None;
+        }
+
+        // Requests end at "now" when they are made, a little before the refresh
+        let covered_to_refresh = chrono::Duration::from_std(self.ttl)
+            .ok()
+            .and_then(|ttl| entry.covered.end.checked_add_signed(ttl))
+            .is_none_or(|end| end >= entry.refreshed_at);
+        if !entry.stale && covered_to_refresh {
+            return None;
+        }
+
+        let tail_start = entry
+            .last_bar
+            .map(|date| date.and_utc())
+            .unwrap_or(entry.covered.end)
+            .max(range.start);
+
+        Some(DateRange {
+            start: tail_start,
+            end: range.end,
+        })
+    }
+
+    /// Returns the cached bars of a token within a range, in chronological order.
+    ///
+    /// The bar the range starts in is included, as providers return it too.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source the bars were fetched from.
+    /// * `token` - The cached token.
+    /// * `interval` - The cached bar interval.
+    /// * `range` - The range of bars to return.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<Bar>, anyhow::Error>` - The cached bars or an error.
+    pub fn bars(
+        &self,
+        source: SourceKind,
+        token: &Token,
+        interval: Interval,
+        range: &DateRange,
+    ) -> Result<Vec<Bar>, anyhow::Error> {
+        let connection = self.lock()?;
+        let mut statement = connection.prepare(
+            "SELECT date, open, high, low, close, volume, adjclose FROM bars
+             WHERE source = ?1 AND symbol = ?2 AND interval = ?3 AND date >= ?4 AND date <= ?5
+             ORDER BY date",
+        )?;
+
+        let bars = statement
+            .query_map(
+                params![
+                    source.id(),
+                    token.id(),
+                    interval.id(),
+                    interval.bar_start(range.start).and_utc().timestamp(),
+                    range.end.timestamp()
+                ],
+                |row| {
+                    Ok(Bar {
+                        date: Self::from_timestamp(row.get(0)?),
+                        open: row.get(1)?,
+                        high: row.get(2)?,
+                        low: row.get(3)?,
+                        close: row.get(4)?,
+                        volume: row.get(5)?,
+                        adjclose: row.get(6)?,
+                    })
+                },
+            )?
+            .collect::<Result<Vec<Bar>, rusqlite::Error>>()?;
+
+        Ok(bars)
+    }
+
+    /// Stores freshly fetched bars and extends the covered range.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source the bars were fetched from.
+    /// * `token` - The fetched token.
+    /// * `interval` - The fetched bar interval.
+    /// * `fetched` - The range the bars were fetched for.
+    /// * `bars` - The fetched bars.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), anyhow::Error>` - An error if the bars could not be stored.
+    pub fn store(
+        &self,
+        source: SourceKind,
+        token: &Token,
+        interval: Interval,
+        fetched: &DateRange,
+        bars: &[Bar],
+    ) -> Result<(), anyhow::Error> {
+        let previous = self.entry(source, token, interval)?;
+        let mut connection = self.lock()?;
+        let transaction = connection.transaction()?;
+
+        {
+            let mut statement = transaction.prepare(
+                "INSERT OR REPLACE INTO bars
+                 (source, symbol, interval, date, open, high, low, close, volume, adjclose)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            )?;
+
+            for bar in bars {
+                statement.execute(params![
+                    source.id(),
+                    token.id(),
+                    interval.id(),
+                    bar.date.and_utc().timestamp(),
+                    bar.open,
+                    bar.high,
+                    bar.low,
+                    bar.close,
+                    bar.volume,
+                    bar.adjclose
+                ])?;
+            }
+        }
+
+        // Only extend the covered range when the fetch is contiguous with it
+        let covered = match previous {
+            Some(entry)
+                if fetched.start <= entry.covered.end && fetched.end >= entry.covered.start =>
+            {
+                DateRange {
+                    start: fetched.start.min(entry.covered.start),
+                    end: fetched.end.max(entry.covered.end),
+                }
+            }
+            _ => *fetched,
+        };
+
+        transaction.execute(
+            "INSERT OR REPLACE INTO entries
+             (source, symbol, interval, covered_from, covered_to, refreshed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                source.id(),
+                token.id(),
+                interval.id(),
+                covered.start.timestamp(),
+                covered.end.timestamp(),
+                Utc::now().timestamp()
+            ],
+        )?;
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    /// Removes cached bars and entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Only purge this source, all sources if `None`.
+    /// * `symbol` - Only purge this symbol, all symbols if `None`.
+    /// * `interval` - Only purge this interval, all intervals if `None`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, anyhow::Error>` - The number of purged entries or an error.
+    pub fn purge(
+        &self,
+        source: Option<SourceKind>,
+        symbol: Option<&str>,
+        interval: Option<Interval>,
+    ) -> Result<usize, anyhow::Error> {
+        let source = source.map(|source| source.id().to_string());
+        let interval = interval.map(|interval| interval.id().to_string());
+        let filter = "(?1 IS NULL OR source = ?1) AND (?2 IS NULL OR symbol = ?2)
+             AND (?3 IS NULL OR interval = ?3)";
+
+        let mut connection = self.lock()?;
+        let transaction = connection.transaction()?;
+
+        transaction.execute(
+            &format!("DELETE FROM bars WHERE {}", filter),
+            params![source, symbol, interval],
+        )?;
+        let purged = transaction.execute(
+            &format!("DELETE FROM entries WHERE {}", filter),
+            params![source, symbol, interval],
+        )?;
+
+        transaction.commit()?;
+
+        Ok(purged)
+    }
+
+    /// Queries cache entries, optionally filtered by source, symbol and interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - Only return this source, all sources if `None`.
+    /// * `symbol` - Only return this symbol, all symbols if `None`.
+    /// * `interval` - Only return this interval, all intervals if `None`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<CacheEntry>, anyhow::Error>` - The matching entries or an error.
+    fn query_entries(
+        &self,
+        source: Option<SourceKind>,
+        symbol: Option<&str>,
+        interval: Option<Interval>,
+    ) -> Result<Vec<CacheEntry>, anyhow::Error> {
+        let source = source.map(|source| source.id().to_string());
+        let interval = interval.map(|interval| interval.id().to_string());
+        let connection = self.lock()?;
+        let mut statement = connection.prepare(
+            "SELECT e.source, e.symbol, e.interval, e.covered_from, e.covered_to, e.refreshed_at,
+                    COUNT(b.date), MIN(b.date), MAX(b.date)
+             FROM entries e
+             LEFT JOIN bars b
+                 ON b.source = e.source AND b.symbol = e.symbol AND b.interval = e.interval
+             WHERE (?1 IS NULL OR e.source = ?1) AND (?2 IS NULL OR e.symbol = ?2)
+                 AND (?3 IS NULL OR e.interval = ?3)
+             GROUP BY e.source, e.symbol, e.interval
+             ORDER BY e.source, e.symbol, e.interval",
+        )?;
+
+        let now = Utc::now();
+        let ttl = chrono::Duration::from_std(self.ttl)?;
+
+        let entries = statement
+            .query_map(params![source, symbol, interval], |row| {
+                let refreshed_at = Self::from_timestamp(row.get(5)?).and_utc();
+
+                Ok(CacheEntry {
+                    source: row.get(0)?,
+                    symbol: row.get(1)?,
+                    interval: row.get(2)?,
+                    covered: DateRange {
+                        start: Self::from_timestamp(row.get(3)?).and_utc(),
+                        end: Self::from_timestamp(row.get(4)?).and_utc(),
+                    },
+                    refreshed_at,
+                    stale: now - refreshed_at >= ttl,
+                    bars: row.get::<_, i64>(6)? as usize,
+                    first_bar: row.get::<_, Option<i64>>(7)?.map(Self::from_timestamp),
+                    last_bar: row.get::<_, Option<i64>>(8)?.map(Self::from_timestamp),
+                })
+            })?
+            .collect::<Result<Vec<CacheEntry>, rusqlite::Error>>()?;
+
+        Ok(entries)
+    }
+
+    /// Locks the database connection.
+    fn lock(&self) -> Result<MutexGuard<'_, Connection>, anyhow::Error> {
+        self.connection
+            .lock()
+            .map_err(|_| anyhow!("The price cache connection is poisoned."))
+    }
+
+    /// Converts a stored unix timestamp back into a naive UTC date time.
+    fn from_timestamp(timestamp: i64) -> NaiveDateTime {
+        DateTime::from_timestamp(timestamp, 0)
+            .unwrap_or_default()
+            .naive_utc()
+    }
+}
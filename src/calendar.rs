@@ -0,0 +1,228 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Trading calendar an asset follows, which determines how many bars make up a year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TradingCalendar {
+    /// Exchange-traded assets with ~252 sessions per year, following the NYSE holidays.
+    Equity,
+    /// Assets trading around the clock, every day of the year.
+    Crypto,
+}
+
+impl TradingCalendar {
+    /// Returns the calendar shared by two assets once their series are aligned.
+    ///
+    /// Aligning a 24/7 asset with an exchange-traded one keeps only the exchange
+    /// sessions, so the more restrictive calendar wins.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The calendar of the other asset.
+    ///
+    /// # Returns
+    ///
+    /// * `TradingCalendar` - The common calendar.
+    pub fn common(self, other: TradingCalendar) -> TradingCalendar {
+        match (self, other) {
+            (TradingCalendar::Crypto, TradingCalendar::Crypto) => TradingCalendar::Crypto,
+            _ => TradingCalendar::Equity,
+        }
+    }
+
+    /// Returns the calendar of two assets once their series are merged onto the union of their bars.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The calendar of the other asset.
+    ///
+    /// # Returns
+    ///
+    /// * `TradingCalendar` - The union calendar.
+    pub fn union(self, other: TradingCalendar) -> TradingCalendar {
+        match (self, other) {
+            (TradingCalendar::Equity, TradingCalendar::Equity) => TradingCalendar::Equity,
+            _ => TradingCalendar::Crypto,
+        }
+    }
+
+    /// Returns whether the calendar trades on a date.
+    ///
+    /// Equity sessions skip weekends and the scheduled NYSE holidays;
+    /// unscheduled closures (national days of mourning, weather) are not known.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The date to check.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the date is a trading session.
+    pub fn is_session(&self, date: NaiveDate) -> bool {
+        match self {
+            TradingCalendar::Crypto => true,
+            TradingCalendar::Equity => {
+                !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+                    && !Self::nyse_holidays(date.year()).contains(&date)
+            }
+        }
+    }
+
+    /// Returns the first session after a date.
+    pub fn next_session(&self, date: NaiveDate) -> NaiveDate {
+        let mut next = date + Duration::days(1);
+        while !self.is_session(next) {
+            next += Duration::days(1);
+        }
+        next
+    }
+
+    /// Returns the full-day NYSE holidays of a year, on the weekday they are observed.
+    ///
+    /// Holidays falling on a Saturday are observed on the Friday before and
+    /// those falling on a Sunday on the Monday after, except New Year's Day,
+    /// which is not observed on the last day of the previous year.
+    fn nyse_holidays(year: i32) -> Vec<NaiveDate> {
+        let date = |month, day| NaiveDate::from_ymd_opt(year, month, day).expect("valid holiday");
+        let nth = |month, weekday, n| {
+            NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).expect("valid weekday")
+        };
+        let observed = |holiday: NaiveDate| match holiday.weekday() {
+            Weekday::Sat => holiday - Duration::days(1),
+            Weekday::Sun => holiday + Duration::days(1),
+            _ => holiday,
+        };
+
+        let last_monday_of_may = {
+            let fifth = NaiveDate::from_weekday_of_month_opt(year, 5, Weekday::Mon, 5);
+            fifth.unwrap_or_else(|| nth(5, Weekday::Mon, 4))
+        };
+
+        let mut holidays = vec![
+            nth(2, Weekday::Mon, 3),
+            Self::easter(year) - Duration::days(2),
+            last_monday_of_may,
+            observed(date(7, 4)),
+            nth(9, Weekday::Mon, 1),
+            nth(11, Weekday::Thu, 4),
+            observed(date(12, 25)),
+        ];
+
+        if date(1, 1).weekday() != Weekday::Sat {
+            holidays.push(observed(date(1, 1)));
+        }
+        if year >= 1998 {
+            holidays.push(nth(1, Weekday::Mon, 3));
+        }
+        if year >= 2022 {
+            holidays.push(observed(date(6, 19)));
+        }
+
+        holidays
+    }
+
+    /// Returns the date of Easter Sunday with the anonymous Gregorian algorithm.
+    fn easter(year: i32) -> NaiveDate {
+        let (a, b, c) = (year % 19, year / 100, year % 100);
+        let g = (b - (b + 8) / 25 + 1) / 3;
+        let h = (19 * a + b - b / 4 - g + 15) % 30;
+        let l = (32 + 2 * (b % 4) + 2 * (c / 4) - h - c % 4) % 7;
+        let m = (a + 11 * h + 22 * l) / 451;
+        let f = h + l - 7 * m + 114;
+
+        NaiveDate::from_ymd_opt(year, (f / 31) as u32, (f % 31 + 1) as u32).expect("valid Easter")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TradingCalendar;
+    use chrono::{Datelike, Duration, NaiveDate};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn nyse_holidays_of_a_year() {
+        let mut holidays = TradingCalendar::nyse_holidays(2024);
+        holidays.sort();
+
+        assert_eq!(
+            holidays,
+            vec![
+                date(2024, 1, 1),
+                date(2024, 1, 15),
+                date(2024, 2, 19),
+                date(2024, 3, 29),
+                date(2024, 5, 27),
+                date(2024, 6, 19),
+                date(2024, 7, 4),
+                date(2024, 9, 2),
+                date(2024, 11, 28),
+                date(2024, 12, 25),
+            ]
+        );
+    }
+
+    #[test]
+    fn weekend_holidays_are_observed_on_the_nearest_weekday() {
+        let equity = TradingCalendar::Equity;
+
+        // Sunday holidays move to Monday
+        assert!(!equity.is_session(date(2021, 7, 5)));
+        assert!(!equity.is_session(date(2022, 6, 20)));
+        assert!(!equity.is_session(date(2022, 12, 26)));
+
+        // Saturday holidays move to Friday
+        assert!(!equity.is_session(date(2021, 12, 24)));
+        assert!(!equity.is_session(date(2026, 7, 3)));
+
+        // Except New Year's Day, which leaves the last session of the year open
+        assert!(equity.is_session(date(2021, 12, 31)));
+        assert!(equity.is_session(date(2022, 1, 3)));
+
+        // Juneteenth is only a holiday since 2022
+        assert!(equity.is_session(date(2021, 6, 18)));
+    }
+
+    #[test]
+    fn good_friday_follows_easter() {
+        let easter_sundays = [
+            date(2008, 3, 23),
+            date(2019, 4, 21),
+            date(2024, 3, 31),
+            date(2025, 4, 20),
+            date(2038, 4, 25),
+        ];
+
+        for easter in easter_sundays {
+            assert_eq!(TradingCalendar::easter(easter.year()), easter);
+            assert!(!TradingCalendar::Equity.is_session(easter - Duration::days(2)));
+        }
+    }
+
+    #[test]
+    fn next_session_skips_weekends_and_holidays() {
+        let equity = TradingCalendar::Equity;
+
+        // Good Friday and the Easter weekend, across a month end
+        assert_eq!(equity.next_session(date(2024, 3, 28)), date(2024, 4, 1));
+        assert_eq!(equity.next_session(date(2024, 11, 27)), date(2024, 11, 29));
+        assert_eq!(
+            TradingCalendar::Crypto.next_session(date(2024, 3, 28)),
+            date(2024, 3, 29)
+        );
+    }
+
+    #[test]
+    fn aligned_calendars() {
+        let (equity, crypto) = (TradingCalendar::Equity, TradingCalendar::Crypto);
+
+        assert_eq!(crypto.common(equity), equity);
+        assert_eq!(crypto.common(crypto), crypto);
+        assert_eq!(crypto.union(equity), crypto);
+        assert_eq!(equity.union(equity), equity);
+    }
+}
@@ -0,0 +1,298 @@
+use crate::asof::AsOfClose;
+use crate::limit::RateLimit;
+use crate::request::RetryPolicy;
+use crate::source::file::{ColumnMapping, FileFormat, FileSourceConfig};
+use crate::source::yahoo::session::HeaderProfile;
+use crate::source::SourceKind;
+use chrono_tz::Tz;
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Default location of the on-disk price cache.
+const DEFAULT_CACHE_PATH: &str = "price_cache.sqlite3";
+
+/// Default time a cached series is served before its tail is refreshed.
+const DEFAULT_CACHE_TTL_SECS: u64 = 15 * 60;
+
+/// Runtime settings read from the environment (and `.env`, if present).
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Location of the price cache, `None` when caching is disabled.
+    pub cache_path: Option<PathBuf>,
+    /// Time a cached series is considered fresh.
+    pub cache_ttl: Duration,
+    /// Source used when a request does not name one.
+    pub price_source: SourceKind,
+    /// Optional CoinGecko demo API key.
+    pub coingecko_api_key: Option<String>,
+    /// Directory, format, column mapping and timezone of the file source.
+    pub file_source: FileSourceConfig,
+    /// Browser headers sent with every Yahoo request.
+    pub yahoo_headers: HeaderProfile,
+    /// Attempts, timeout and backoff of the requests to each provider.
+    pub retry_policies: HashMap<SourceKind, RetryPolicy>,
+    /// Rate limit of the requests to each provider, `None` when unlimited.
+    pub rate_limits: HashMap<SourceKind, Option<RateLimit>>,
+    /// Time daily crypto closes are sampled at, `None` to date bars by their UTC open.
+    pub crypto_close: Option<AsOfClose>,
+}
+
+impl Config {
+    /// Reads the configuration from the environment.
+    ///
+    /// * `PRICE_CACHE_ENABLED` - Set to `false` to disable the price cache.
+    /// * `PRICE_CACHE_PATH` - Location of the SQLite cache file.
+    /// * `PRICE_CACHE_TTL_SECS` - Seconds before a cached series is refreshed.
+    /// * `PRICE_SOURCE` - Default price source (`yahoo`, `coingecko`, `binance`, `stooq`, `fred`, `file`).
+    /// * `COINGECKO_API_KEY` - CoinGecko demo API key.
+    /// * `FILE_SOURCE_DIR` - Directory of the `<SYMBOL>.csv` / `<SYMBOL>.parquet` files.
+    /// * `FILE_SOURCE_FORMAT` - Only read `csv` or `parquet` files.
+    /// * `FILE_SOURCE_COLUMNS` - Column mapping, e.g. `date=Timestamp,close=Last`.
+    /// * `FILE_SOURCE_TIMEZONE` - IANA timezone of naive dates in the files.
+    /// * `CRYPTO_CLOSE_TIME` / `CRYPTO_CLOSE_TIMEZONE` - As-of close of daily crypto bars, e.g. `16:00` and `America/New_York` (defaults to UTC).
+    /// * `YAHOO_USER_AGENT` - User agent sent to Yahoo.
+    /// * `YAHOO_HEADERS` - JSON object of extra headers sent to Yahoo, empty values remove defaults.
+    /// * `HTTP_ATTEMPTS` / `HTTP_TIMEOUT_SECS` - Attempts and per-attempt timeout of provider requests.
+    /// * `<SOURCE>_HTTP_ATTEMPTS` / `<SOURCE>_HTTP_TIMEOUT_SECS` - The same for one provider, e.g. `YAHOO_HTTP_ATTEMPTS`.
+    /// * `<SOURCE>_RATE_LIMIT` / `<SOURCE>_RATE_BURST` - Requests per second (`0` disables) and burst size of one provider.
+    ///
+    /// # Returns
+    ///
+    /// * `Config` - The configuration, with defaults for missing or invalid values.
+    pub fn from_env() -> Config {
+        let cache_enabled = env::var("PRICE_CACHE_ENABLED")
+            .map(|value| value != "false" && value != "0")
+            .unwrap_or(true);
+
+        let cache_path = cache_enabled.then(|| {
+            env::var("PRICE_CACHE_PATH")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from(DEFAULT_CACHE_PATH))
+        });
+
+        let cache_ttl = Duration::from_secs(Self::parse_var(
+            "PRICE_CACHE_TTL_SECS",
+            DEFAULT_CACHE_TTL_SECS,
+        ));
+
+        let price_source = match env::var("PRICE_SOURCE") {
+            Ok(value) => SourceKind::from_str(&value).unwrap_or_else(|| {
+                warn!("Invalid PRICE_SOURCE value <{}>, using yahoo.", value);
+                SourceKind::Yahoo
+            }),
+            Err(_) => SourceKind::Yahoo,
+        };
+
+        Config {
+            cache_path,
+            cache_ttl,
+            price_source,
+            coingecko_api_key: env::var("COINGECKO_API_KEY").ok(),
+            file_source: Self::file_source(),
+            yahoo_headers: Self::yahoo_headers(),
+            retry_policies: Self::retry_policies(),
+            rate_limits: Self::rate_limits(),
+            crypto_close: Self::crypto_close(),
+        }
+    }
+
+    /// Reads the as-of close of daily crypto bars from the environment.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<AsOfClose>` - The close, `None` when it is not set or invalid.
+    fn crypto_close() -> Option<AsOfClose> {
+        let time = env::var("CRYPTO_CLOSE_TIME").ok()?;
+        let timezone = env::var("CRYPTO_CLOSE_TIMEZONE").unwrap_or_else(|_| "UTC".to_string());
+
+        let close = AsOfClose::from_str(&time, &timezone);
+        if close.is_none() {
+            warn!(
+                "Invalid CRYPTO_CLOSE_TIME <{}> or CRYPTO_CLOSE_TIMEZONE <{}>, dating crypto bars in UTC.",
+                time, timezone
+            );
+        }
+
+        close
+    }
+
+    /// Returns the rate limit of a provider, `None` when unlimited.
+    pub fn rate_limit(&self, source: SourceKind) -> Option<RateLimit> {
+        self.rate_limits.get(&source).copied().flatten()
+    }
+
+    /// Reads the rate limit of every provider from the environment.
+    ///
+    /// Defaults stay below the documented or observed limits of each provider.
+    ///
+    /// # Returns
+    ///
+    /// * `HashMap<SourceKind, Option<RateLimit>>` - The rate limits, `None` when unlimited.
+    fn rate_limits() -> HashMap<SourceKind, Option<RateLimit>> {
+        SourceKind::ALL
+            .into_iter()
+            .map(|source| {
+                let (per_second, burst) = match source {
+                    SourceKind::Yahoo => (2.0, 5),
+                    // The demo plan allows 30 calls per minute
+                    SourceKind::CoinGecko => (0.5, 5),
+                    SourceKind::Binance => (10.0, 20),
+                    SourceKind::Stooq => (1.0, 2),
+                    SourceKind::Fred => (2.0, 5),
+                    SourceKind::File => (0.0, 0),
+                };
+
+                let prefix = source.id().to_uppercase();
+                let per_second = match env::var(format!("{}_RATE_LIMIT", prefix)) {
+                    Ok(value) => value.parse::<f64>().unwrap_or_else(|_| {
+                        warn!(
+                            "Invalid {}_RATE_LIMIT value <{}>, using {}.",
+                            prefix, value, per_second
+                        );
+                        per_second
+                    }),
+                    Err(_) => per_second,
+                };
+                let burst = Self::parse_var(&format!("{}_RATE_BURST", prefix), burst);
+
+                let limit = (per_second > 0.0).then(|| RateLimit {
+                    per_second,
+                    burst: burst.clamp(1, u32::MAX as u64) as u32,
+                });
+
+                (source, limit)
+            })
+            .collect()
+    }
+
+    /// Returns the retry policy of a provider.
+    pub fn retry_policy(&self, source: SourceKind) -> RetryPolicy {
+        self.retry_policies
+            .get(&source)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Reads the retry policy of every provider from the environment.
+    ///
+    /// # Returns
+    ///
+    /// * `HashMap<SourceKind, RetryPolicy>` - The policies, provider settings overriding the global ones.
+    fn retry_policies() -> HashMap<SourceKind, RetryPolicy> {
+        let default = RetryPolicy::default();
+        let attempts = Self::parse_var("HTTP_ATTEMPTS", default.attempts as u64);
+        let timeout = Self::parse_var("HTTP_TIMEOUT_SECS", default.timeout.as_secs());
+
+        SourceKind::ALL
+            .into_iter()
+            .map(|source| {
+                let prefix = source.id().to_uppercase();
+                let attempts = Self::parse_var(&format!("{}_HTTP_ATTEMPTS", prefix), attempts);
+                let timeout = Self::parse_var(&format!("{}_HTTP_TIMEOUT_SECS", prefix), timeout);
+
+                let policy = RetryPolicy {
+                    attempts: attempts.clamp(1, u32::MAX as u64) as u32,
+                    timeout: Duration::from_secs(timeout.max(1)),
+                    ..default
+                };
+
+                (source, policy)
+            })
+            .collect()
+    }
+
+    /// Reads the Yahoo header profile from the environment.
+    ///
+    /// # Returns
+    ///
+    /// * `HeaderProfile` - The default profile with the configured headers applied, invalid ones skipped.
+    fn yahoo_headers() -> HeaderProfile {
+        let mut headers: Vec<(String, String)> = Vec::new();
+
+        if let Ok(user_agent) = env::var("YAHOO_USER_AGENT") {
+            headers.push(("user-agent".to_string(), user_agent));
+        }
+
+        if let Ok(value) = env::var("YAHOO_HEADERS") {
+            match serde_json::from_str::<HashMap<String, String>>(&value) {
+                Ok(extra) => headers.extend(extra),
+                Err(err) => warn!("Invalid YAHOO_HEADERS value <{}>: {}", value, err),
+            }
+        }
+
+        headers.into_iter().fold(
+            HeaderProfile::default(),
+            |profile, (name, value)| match profile.clone().with_header(&name, &value) {
+                Ok(profile) => profile,
+                Err(err) => {
+                    warn!("Skipping Yahoo header: {}", err);
+                    profile
+                }
+            },
+        )
+    }
+
+    /// Reads the file source settings from the environment.
+    ///
+    /// # Returns
+    ///
+    /// * `FileSourceConfig` - The settings, with defaults for missing or invalid values.
+    fn file_source() -> FileSourceConfig {
+        let mut file_source = FileSourceConfig::default();
+
+        if let Ok(dir) = env::var("FILE_SOURCE_DIR") {
+            file_source.dir = PathBuf::from(dir);
+        }
+
+        if let Ok(value) = env::var("FILE_SOURCE_FORMAT") {
+            file_source.format = FileFormat::from_str(&value);
+            if file_source.format.is_none() {
+                warn!(
+                    "Invalid FILE_SOURCE_FORMAT value <{}>, reading all formats.",
+                    value
+                );
+            }
+        }
+
+        if let Ok(value) = env::var("FILE_SOURCE_COLUMNS") {
+            match ColumnMapping::from_str(&value) {
+                Some(columns) => file_source.columns = columns,
+                None => warn!(
+                    "Invalid FILE_SOURCE_COLUMNS value <{}>, using defaults.",
+                    value
+                ),
+            }
+        }
+
+        if let Ok(value) = env::var("FILE_SOURCE_TIMEZONE") {
+            match value.parse::<Tz>() {
+                Ok(timezone) => file_source.timezone = timezone,
+                Err(_) => warn!("Invalid FILE_SOURCE_TIMEZONE value <{}>, using UTC.", value),
+            }
+        }
+
+        file_source
+    }
+
+    /// Parses a numeric environment variable, falling back to a default.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the environment variable.
+    /// * `default` - The value used when the variable is missing or invalid.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The parsed value or the default.
+    fn parse_var(name: &str, default: u64) -> u64 {
+        match env::var(name) {
+            Ok(value) => value.parse().unwrap_or_else(|_| {
+                warn!("Invalid {} value <{}>, using {}.", name, value, default);
+                default
+            }),
+            Err(_) => default,
+        }
+    }
+}
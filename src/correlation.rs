@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+
+/// Measures of the correlation between two series.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorrelationMethod {
+    /// Linear correlation of the values.
+    #[default]
+    Pearson,
+    /// Pearson correlation of the ranks, robust to outliers and monotonic transforms.
+    Spearman,
+    /// Kendall's tau-b, the balance of concordant and discordant pairs adjusted for ties.
+    Kendall,
+}
+
+impl CorrelationMethod {
+    /// Creates a `CorrelationMethod` enum from a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - A string slice representing the method name.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<CorrelationMethod>` - Representing the method if valid, `None` if invalid.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(method: &str) -> Option<CorrelationMethod> {
+        match method.to_lowercase().as_str() {
+            "pearson" => Some(CorrelationMethod::Pearson),
+            "spearman" => Some(CorrelationMethod::Spearman),
+            "kendall" => Some(CorrelationMethod::Kendall),
+            _ => None,
+        }
+    }
+
+    /// Returns the variance of the Fisher transform of the coefficient over a number of observations.
+    ///
+    /// Rank coefficients use the Fieller, Hartley and Pearson approximations.
+    pub fn fisher_variance(&self, observations: usize) -> f64 {
+        let n = observations as f64;
+        match self {
+            CorrelationMethod::Pearson => 1.0 / (n - 3.0),
+            CorrelationMethod::Spearman => 1.06 / (n - 3.0),
+            CorrelationMethod::Kendall => 0.437 / (n - 4.0),
+        }
+    }
+
+    /// Returns the fewest observations the Fisher confidence interval needs.
+    pub fn min_fisher_observations(&self) -> usize {
+        match self {
+            CorrelationMethod::Kendall => 5,
+            _ => 4,
+        }
+    }
+
+    /// Ranks values, tied values sharing the average of their ranks.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values to rank.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<f64>` - The rank of each value, starting at 1.
+    pub fn ranks(values: &[f64]) -> Vec<f64> {
+        let mut order: Vec<usize> = (0..values.len()).collect();
+        order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+        let mut ranks = vec![0.0; values.len()];
+        let mut start = 0;
+        while start < order.len() {
+            let mut end = start + 1;
+            while end < order.len() && values[order[end]] == values[order[start]] {
+                end += 1;
+            }
+
+            // Ranks `start + 1..=end` are shared by the tied values
+            let rank = (start + end + 1) as f64 / 2.0;
+            for &i in &order[start..end] {
+                ranks[i] = rank;
+            }
+            start = end;
+        }
+
+        ranks
+    }
+
+    /// Calculates Kendall's tau-b of two aligned series in `O(n log n)` with Knight's algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `values_1` - The first series.
+    /// * `values_2` - The second series, aligned with the first one.
+    ///
+    /// # Returns
+    ///
+    /// * `f64` - The coefficient, `NaN` if either series is constant.
+    pub fn kendall_tau_b(values_1: &[f64], values_2: &[f64]) -> f64 {
+        let mut pairs: Vec<(f64, f64)> = values_1
+            .iter()
+            .copied()
+            .zip(values_2.iter().copied())
+            .collect();
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+        let total = Self::tied_pairs(pairs.len());
+        let ties_1 = Self::count_ties(&pairs, |a, b| a.0 == b.0);
+        let joint_ties = Self::count_ties(&pairs, |a, b| a == b);
+
+        // Sorting by the second value counts the pairs ordered differently by both series
+        let mut ys: Vec<f64> = pairs.iter().map(|pair| pair.1).collect();
+        let mut buffer = vec![0.0; ys.len()];
+        let discordant = Self::merge_sort_swaps(&mut ys, &mut buffer);
+        let ties_2 = Self::count_ties(&ys, |a, b| a == b);
+
+        let numerator = total as f64 - ties_1 as f64 - ties_2 as f64 + joint_ties as f64
+            - 2.0 * discordant as f64;
+        let denominator = ((total - ties_1) as f64 * (total - ties_2) as f64).sqrt();
+
+        numerator / denominator
+    }
+
+    /// Clips the values of a series to the quantiles leaving a fraction of values in each tail.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The series.
+    /// * `fraction` - The fraction of values clipped in each tail, below 0.5.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<f64>` - The winsorized series.
+    pub fn winsorize(values: &[f64], fraction: f64) -> Vec<f64> {
+        if values.is_empty() {
+            return Vec::new();
+        }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let clipped = (fraction * values.len() as f64).floor() as usize;
+        let (lower, upper) = (sorted[clipped], sorted[sorted.len() - 1 - clipped]);
+
+        values.iter().map(|v| v.clamp(lower, upper)).collect()
+    }
+
+    /// Returns the number of pairs among a number of items.
+    fn tied_pairs(len: usize) -> u64 {
+        let len = len as u64;
+        len * len.saturating_sub(1) / 2
+    }
+
+    /// Counts the pairs within runs of equal consecutive items of a sorted slice.
+    fn count_ties<T, F: Fn(&T, &T) -> bool>(sorted: &[T], equal: F) -> u64 {
+        let mut ties = 0;
+        let mut run = 1;
+
+        for i in 1..=sorted.len() {
+            if i < sorted.len() && equal(&sorted[i - 1], &sorted[i]) {
+                run += 1;
+            } else {
+                ties += Self::tied_pairs(run);
+                run = 1;
+            }
+        }
+
+        ties
+    }
+
+    /// Sorts values with a merge sort, counting the swaps a bubble sort would make.
+    fn merge_sort_swaps(values: &mut [f64], buffer: &mut [f64]) -> u64 {
+        let len = values.len();
+        if len < 2 {
+            return 0;
+        }
+
+        let mid = len / 2;
+        let mut swaps = Self::merge_sort_swaps(&mut values[..mid], &mut buffer[..mid])
+            + Self::merge_sort_swaps(&mut values[mid..], &mut buffer[mid..]);
+
+        let (mut i, mut j) = (0, mid);
+        for slot in buffer[..len].iter_mut() {
+            if j >= len || (i < mid && values[i] <= values[j]) {
+                *slot = values[i];
+                i += 1;
+            } else {
+                // Every value left in the first half is greater than this one
+                *slot = values[j];
+                swaps += (mid - i) as u64;
+                j += 1;
+            }
+        }
+
+        values.copy_from_slice(&buffer[..len]);
+        swaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CorrelationMethod;
+    use std::cmp::Ordering;
+
+    /// Counts concordant minus discordant pairs and the pairs untied in each series, in `O(n²)`.
+    fn brute_force_tau_b(values_1: &[f64], values_2: &[f64]) -> f64 {
+        let sign = |values: &[f64], i: usize, j: usize| match values[i].total_cmp(&values[j]) {
+            Ordering::Less => -1.0,
+            Ordering::Equal => 0.0,
+            Ordering::Greater => 1.0,
+        };
+        let (mut balance, mut untied_1, mut untied_2) = (0.0_f64, 0.0, 0.0);
+
+        for i in 0..values_1.len() {
+            for j in i + 1..values_1.len() {
+                let (sign_1, sign_2) = (sign(values_1, i, j), sign(values_2, i, j));
+                balance += sign_1 * sign_2;
+                untied_1 += sign_1 * sign_1;
+                untied_2 += sign_2 * sign_2;
+            }
+        }
+
+        balance / (untied_1 * untied_2).sqrt()
+    }
+
+    #[test]
+    fn kendall_tau_b_matches_brute_force_with_ties() {
+        let values_1 = [1.0, 2.0, 2.0, 3.0, 4.0, 4.0, 4.0, 5.0, 6.0, 1.0, 7.0, 3.0];
+        let values_2 = [2.0, 1.0, 1.0, 3.0, 5.0, 4.0, 5.0, 2.0, 6.0, 6.0, 7.0, 3.0];
+
+        let tau = CorrelationMethod::kendall_tau_b(&values_1, &values_2);
+        let expected = brute_force_tau_b(&values_1, &values_2);
+
+        assert!((tau - expected).abs() < 1e-12, "{} != {}", tau, expected);
+    }
+
+    #[test]
+    fn kendall_tau_b_of_monotonic_series() {
+        let values_1 = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let increasing = [10.0, 20.0, 30.0, 40.0, 50.0];
+        let decreasing = [5.0, 4.0, 3.0, 2.0, 1.0];
+
+        assert_eq!(
+            CorrelationMethod::kendall_tau_b(&values_1, &increasing),
+            1.0
+        );
+        assert_eq!(
+            CorrelationMethod::kendall_tau_b(&values_1, &decreasing),
+            -1.0
+        );
+        assert!(CorrelationMethod::kendall_tau_b(&values_1, &[1.0; 5]).is_nan());
+    }
+
+    #[test]
+    fn ranks_average_ties() {
+        let ranks = CorrelationMethod::ranks(&[3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 5.0]);
+
+        assert_eq!(ranks, vec![4.0, 1.5, 5.0, 1.5, 6.5, 8.0, 3.0, 6.5]);
+    }
+
+    #[test]
+    fn winsorize_clips_tails() {
+        let values: Vec<f64> = (1..=10).map(f64::from).collect();
+
+        assert_eq!(
+            CorrelationMethod::winsorize(&values, 0.2),
+            vec![3.0, 3.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 8.0, 8.0]
+        );
+        assert_eq!(CorrelationMethod::winsorize(&values, 0.0), values);
+    }
+}
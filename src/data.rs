@@ -1,16 +1,42 @@
-use crate::request::Request;
+use crate::alignment::Alignment;
+use crate::asof::AsOfClose;
+use crate::basis::Basis;
+use crate::cache::PriceCache;
+use crate::calendar::TradingCalendar;
+use crate::correlation::CorrelationMethod;
+use crate::error::{FetchError, InsufficientData};
+use crate::estimation::Estimation;
+use crate::estimator::Estimator;
+use crate::forecast::{FittedModel, ForecastModel};
+use crate::interval::Interval;
+use crate::metrics::MetricsSnapshot;
+use crate::range::DateRange;
+use crate::regression::{GrangerTest, Regression};
+use crate::series::{Bar, DataQuality, PriceSeries};
+use crate::source::{PriceSource, SourceKind, SourceRegistry};
+use crate::token::Token;
 use anyhow::anyhow;
-use chrono::{DateTime, Duration, Local, NaiveDate};
+use chrono::NaiveDateTime;
+use futures::future::try_join_all;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use statrs::distribution::{ContinuousCDF, Normal, StudentsT};
+use std::sync::Arc;
 
-use reqwest::{
-    header::{self, HeaderMap},
-    Method,
-};
+/// Default confidence level of correlation confidence intervals.
+pub const DEFAULT_CONFIDENCE: f64 = 0.95;
+
+/// A square matrix, rows and columns following the order of the tokens.
+type Matrix = Vec<Vec<f64>>;
 
 /// Struct to handle historical data processing.
-pub struct HistoricalData;
+#[derive(Clone)]
+pub struct HistoricalData {
+    sources: Arc<SourceRegistry>,
+    source: Arc<dyn PriceSource>,
+    cache: Option<Arc<PriceCache>>,
+    alignment: Alignment,
+    crypto_close: Option<AsOfClose>,
+}
 
 #[allow(unused)]
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,155 +44,899 @@ pub struct HistoricalData;
 pub struct HistoricalDataCovariance {
     pub token_1: Token,
     pub token_2: Token,
+    pub range: DateRange,
+    pub source: SourceKind,
+    pub interval: Interval,
+    pub alignment: Alignment,
+    pub basis: Basis,
+    pub annualization_factor: f64,
+    pub estimation: Estimation,
+    pub method: CorrelationMethod,
+    /// Fraction of values clipped in each tail before estimating, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winsorize: Option<f64>,
+    pub observations: usize,
     pub covariance: f64,
     pub correlation_coefficient: f64,
+    /// `None` with too few observations or a perfect correlation.
+    pub significance: Option<CorrelationSignificance>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ewma: Option<EwmaCovariance>,
+    pub data_quality: Vec<DataQuality>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the statistical significance of a correlation coefficient.
+pub struct CorrelationSignificance {
+    pub test: SignificanceTest,
+    /// Statistic of the null hypothesis of no correlation.
+    pub statistic: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub degrees_of_freedom: Option<f64>,
+    /// Two-sided p-value of the statistic.
+    pub p_value: f64,
+    pub confidence_level: f64,
+    /// Fisher-z confidence interval of the correlation coefficient.
+    pub confidence_interval: [f64; 2],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+/// Distribution of a significance statistic under the null hypothesis.
+pub enum SignificanceTest {
+    /// Student's t with `n - 2` degrees of freedom, for Pearson and Spearman coefficients.
+    StudentT,
+    /// Standard normal, for Kendall coefficients.
+    Normal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the exponentially-weighted covariance and correlation coefficient between two tokens.
+pub struct EwmaCovariance {
+    pub half_life: f64,
+    pub lambda: f64,
+    pub covariance: f64,
+    pub correlation_coefficient: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the regression of the returns of a token on the returns of another.
+///
+/// Alpha, its standard error and the residual volatility are annualized,
+/// beta and R² have no unit.
+pub struct HistoricalDataBeta {
+    pub token_1: Token,
+    pub token_2: Token,
+    pub range: DateRange,
+    pub source: SourceKind,
+    pub interval: Interval,
+    pub alignment: Alignment,
+    pub basis: Basis,
+    pub annualization_factor: f64,
+    pub observations: usize,
+    pub beta: f64,
+    pub beta_standard_error: f64,
+    pub alpha: f64,
+    pub alpha_standard_error: f64,
+    pub r_squared: f64,
+    pub residual_volatility: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rolling: Option<RollingBeta>,
+    pub data_quality: Vec<DataQuality>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the beta of a token over a rolling window.
+pub struct RollingBeta {
+    #[serde(flatten)]
+    pub window: RollingWindow,
+    pub series: Vec<RollingPoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the lead–lag relationship between two tokens.
+///
+/// The correlation at lag `k` pairs `token_1` at bar `t` with `token_2` at
+/// bar `t + k`: a peak at a positive lag means `token_1` leads `token_2`, a
+/// peak at a negative lag means `token_2` leads `token_1`.
+pub struct HistoricalDataCrossCorrelation {
+    pub token_1: Token,
+    pub token_2: Token,
+    pub range: DateRange,
+    pub source: SourceKind,
+    pub interval: Interval,
+    pub alignment: Alignment,
+    pub basis: Basis,
+    pub observations: usize,
+    #[serde(flatten)]
+    pub lags: CrossCorrelationLags,
+    pub correlations: Vec<LagCorrelation>,
+    /// Lag of the correlation with the largest magnitude.
+    pub peak_lag: Option<i64>,
+    pub granger: GrangerCausality,
+    pub data_quality: Vec<DataQuality>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// Lags of a cross-correlation analysis, in bars.
+pub struct CrossCorrelationLags {
+    /// Largest lag of the cross-correlation, in both directions.
+    pub max_lag: usize,
+    /// Number of lags of both series in the Granger-causality models.
+    pub granger_lags: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the correlation between two tokens at a lag, in bars.
+pub struct LagCorrelation {
+    pub lag: i64,
+    /// Number of pairs overlapping at this lag.
+    pub observations: usize,
+    pub correlation: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the Granger-causality tests between two tokens, in both directions.
+pub struct GrangerCausality {
+    /// Whether past values of `token_1` help predict `token_2`.
+    pub token_1_causes_token_2: GrangerTest,
+    /// Whether past values of `token_2` help predict `token_1`.
+    pub token_2_causes_token_1: GrangerTest,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the covariance and correlation matrices of several tokens.
+///
+/// Rows and columns follow the order of `tokens`.
+pub struct HistoricalDataMatrix {
+    pub tokens: Vec<Token>,
+    pub range: DateRange,
+    pub source: SourceKind,
+    pub interval: Interval,
+    pub alignment: Alignment,
+    pub basis: Basis,
+    pub annualization_factor: f64,
+    pub estimation: Estimation,
+    pub method: CorrelationMethod,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winsorize: Option<f64>,
+    pub observations: usize,
+    pub covariance: Vec<Vec<f64>>,
+    pub correlation: Vec<Vec<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ewma: Option<EwmaMatrix>,
+    pub data_quality: Vec<DataQuality>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the exponentially-weighted covariance and correlation matrices of several tokens.
+pub struct EwmaMatrix {
+    pub half_life: f64,
+    pub lambda: f64,
+    pub covariance: Vec<Vec<f64>>,
+    pub correlation: Vec<Vec<f64>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents a rolling statistic over one or two tokens.
+pub struct HistoricalDataRolling {
+    pub tokens: Vec<Token>,
+    pub range: DateRange,
+    pub source: SourceKind,
+    pub interval: Interval,
+    /// Alignment of the tokens, `None` for a single token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alignment: Option<Alignment>,
+    pub basis: Basis,
+    pub annualization_factor: f64,
+    #[serde(flatten)]
+    pub window: RollingWindow,
+    pub series: Vec<RollingPoint>,
+    pub data_quality: Vec<DataQuality>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// Size and step of a rolling window, in bars.
+pub struct RollingWindow {
+    pub window: usize,
+    pub step: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+/// Options of the covariance and correlation estimates.
+///
+/// The method only changes the correlation coefficients: covariances and
+/// exponentially-weighted estimates stay linear. Winsorizing applies to the
+/// values of every estimate.
+pub struct CorrelationOptions {
+    /// The series (prices or returns) the statistics are computed on.
+    pub basis: Basis,
+    pub estimation: Estimation,
+    pub method: CorrelationMethod,
+    /// Fraction of values clipped in each tail of every series, `None` to keep them.
+    pub winsorize: Option<f64>,
+    /// Half-life in bars of the exponentially-weighted estimates, `None` to skip them.
+    pub half_life: Option<f64>,
+    /// Confidence level of the correlation confidence interval.
+    pub confidence: f64,
+}
+
+impl Default for CorrelationOptions {
+    fn default() -> CorrelationOptions {
+        CorrelationOptions {
+            basis: Basis::default(),
+            estimation: Estimation::default(),
+            method: CorrelationMethod::default(),
+            winsorize: None,
+            half_life: None,
+            confidence: DEFAULT_CONFIDENCE,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the value of a rolling statistic at the last bar of its window.
+pub struct RollingPoint {
+    pub date: NaiveDateTime,
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the realized volatility of a token.
+///
+/// The daily and annualized volatilities are given by the close-to-close
+/// estimator, range-based estimators are only reported when requested.
+pub struct HistoricalDataVolatility {
+    pub token: Token,
+    pub symbol: String,
+    pub range: DateRange,
+    pub source: SourceKind,
+    pub interval: Interval,
+    pub annualization_factor: f64,
+    pub window: SampleWindow,
+    pub estimation: Estimation,
+    pub observations: usize,
+    pub daily_volatility: f64,
+    pub annualized_volatility: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub estimators: Vec<VolatilityEstimate>,
+    pub data_quality: DataQuality,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+/// Represents the first and last bar a statistic was computed on.
+pub struct SampleWindow {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents the volatility of a token given by a range-based estimator.
+pub struct VolatilityEstimate {
+    pub estimator: Estimator,
+    pub observations: usize,
+    pub daily_volatility: f64,
+    pub annualized_volatility: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Represents a conditional volatility model fitted on a token and its forecast.
+pub struct HistoricalDataForecast {
+    pub token: Token,
+    pub range: DateRange,
+    pub source: SourceKind,
+    pub interval: Interval,
+    pub annualization_factor: f64,
+    pub observations: usize,
+    pub parameters: FittedModel,
+    /// Annualized conditional volatility of each bar, given the bars before it.
+    pub conditional_volatility: Vec<RollingPoint>,
+    pub forecast: Vec<ForecastPoint>,
+    pub data_quality: DataQuality,
 }
 
-/// Enum representing supported tokens for data.
 #[derive(Debug, Serialize, Deserialize)]
-pub enum Token {
-    Bitcoin,
-    Ethereum,
-    Solana,
-    Snp500,
+/// Represents the forecast volatility of a bar ahead.
+pub struct ForecastPoint {
+    /// Bars ahead of the last fitted bar.
+    pub horizon: usize,
+    /// Annualized volatility of this bar.
+    pub volatility: f64,
+    /// Volatility over all bars up to this one, not annualized.
+    pub cumulative_volatility: f64,
 }
 
-impl Token {
-    /// Returns the identifier used in Yahoo Finance API for the token.
-    pub fn id(&self) -> &str {
-        match *self {
-            Token::Ethereum => "ETH-USD",
-            Token::Bitcoin => "BTC-USD",
-            Token::Solana => "SOL-USD",
-            Token::Snp500 => "%5EGSPC",
+impl HistoricalData {
+    /// Creates the historical data service, fetching from the default source.
+    ///
+    /// # Arguments
+    ///
+    /// * `sources` - The configured price sources.
+    /// * `cache` - The price cache, `None` to always fetch from the provider.
+    ///
+    /// # Returns
+    ///
+    /// * `HistoricalData` - The service.
+    pub fn new(sources: Arc<SourceRegistry>, cache: Option<Arc<PriceCache>>) -> HistoricalData {
+        HistoricalData {
+            source: sources.default_source(),
+            sources,
+            cache,
+            alignment: Alignment::default(),
+            crypto_close: None,
         }
     }
 
-    /// Creates a `Token` enum from a string.
+    /// Returns a copy of the service fetching from the requested source.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source name, `None` for the default source.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HistoricalData, anyhow::Error>` - The service or an error if the source is unknown.
+    pub fn with_source(&self, source: Option<&str>) -> Result<HistoricalData, anyhow::Error> {
+        let source = match source {
+            Some(source) => {
+                let kind = SourceKind::from_str(source)
+                    .ok_or_else(|| anyhow!("Invalid source value: {}", source))?;
+
+                self.sources
+                    .get(kind)
+                    .ok_or_else(|| anyhow!("Source <{}> is not configured", kind.id()))?
+            }
+            None => self.sources.default_source(),
+        };
+
+        Ok(HistoricalData {
+            source,
+            ..self.clone()
+        })
+    }
+
+    /// Returns a copy of the service aligning the series of several tokens with a policy.
     ///
     /// # Arguments
     ///
-    /// * `token` - A string slice representing the token name.
+    /// * `alignment` - The alignment policy.
     ///
     /// # Returns
     ///
-    /// * `Option<Token>` - Representing the token if valid, `None` if invalid.
-    pub fn from_str(token: &str) -> Option<Token> {
-        match token.to_lowercase().as_str() {
-            "bitcoin" | "btc" => Some(Token::Bitcoin),
-            "snp500" | "snp" => Some(Token::Snp500),
-            "solana" | "sol" => Some(Token::Solana),
-            "ethereum" | "eth" => Some(Token::Ethereum),
-            _ => None,
+    /// * `HistoricalData` - The service using the policy.
+    pub fn with_alignment(&self, alignment: Alignment) -> HistoricalData {
+        HistoricalData {
+            alignment,
+            ..self.clone()
         }
     }
 
-    /// Returns the string representation of the token.
-    pub fn as_string(&self) -> &str {
-        match *self {
-            Token::Ethereum => "Ethereum",
-            Token::Bitcoin => "Bitcoin",
-            Token::Solana => "SOL-USD",
-            Token::Snp500 => "Snp500",
+    /// Returns a copy of the service dating daily crypto bars by an as-of close.
+    ///
+    /// # Arguments
+    ///
+    /// * `crypto_close` - The as-of close, `None` to date bars by their UTC open.
+    ///
+    /// # Returns
+    ///
+    /// * `HistoricalData` - The service using the close.
+    pub fn with_crypto_close(&self, crypto_close: Option<AsOfClose>) -> HistoricalData {
+        HistoricalData {
+            crypto_close,
+            ..self.clone()
         }
     }
-}
 
-impl HistoricalData {
+    /// Returns the kind of the source data is fetched from.
+    pub fn source(&self) -> SourceKind {
+        self.source.kind()
+    }
+
+    /// Returns the fetch and request counters of every source.
+    pub fn metrics(&self) -> Vec<MetricsSnapshot> {
+        self.sources.metrics()
+    }
+
+    /// Returns the price cache, if enabled.
+    pub fn cache(&self) -> Option<&Arc<PriceCache>> {
+        self.cache.as_ref()
+    }
+
     /// Calculates the covariance and correlation coefficient between two tokens based on historical data.
     ///
     /// # Arguments
     ///
     /// * `token_1` - First token.
     /// * `token_2` - Second token.
+    /// * `range` - The time range of historical data to use.
+    /// * `interval` - The bar interval of historical data to use.
+    /// * `options` - The basis, estimation, half-life and confidence level of the estimates.
     ///
     /// # Returns
     ///
     /// * `Result<HistoricalDataCovariance, anyhow::Error>` - Result containing the calculated covariance and correlation, or an error.
     pub async fn calculate_covariance(
+        &self,
         token_1: Token,
         token_2: Token,
+        range: DateRange,
+        interval: Interval,
+        options: CorrelationOptions,
     ) -> Result<HistoricalDataCovariance, anyhow::Error> {
-        let mut token_1_data: HashMap<NaiveDate, f64> =
-            Self::get_yearly_data_by_token(&token_1).await?;
-        let mut token_2_data: HashMap<NaiveDate, f64> =
-            Self::get_yearly_data_by_token(&token_2).await?;
-
-        let token_1_len = token_1_data.len();
-        let token_2_len = token_2_data.len();
-
-        // If lengths differ, trim the larger
-        // dataset to match the smaller one
-        if token_1_len != token_2_len {
-            if token_1_len > token_2_len {
-                token_1_data.retain(|date, _| token_2_data.contains_key(date));
-            } else {
-                token_2_data.retain(|date, _| token_1_data.contains_key(date));
-            }
+        let (token_1_data, token_2_data) = tokio::try_join!(
+            self.get_data_by_token(&token_1, &range, interval),
+            self.get_data_by_token(&token_2, &range, interval),
+        )?;
+
+        let token_data = [token_1_data, token_2_data];
+        let calendars = [token_1.calendar(), token_2.calendar()];
+        let (_, aligned) = self.align_series(&token_data, &calendars, interval);
+        let (token_1_prices, token_2_prices) = (&aligned[0], &aligned[1]);
+
+        if token_1_prices.is_empty() {
+            return Err(InsufficientData(
+                "No common timestamps found between the two tokens.".to_string(),
+            )
+            .into());
         }
 
-        let common_dates: Vec<NaiveDate> = token_1_data
-            .keys()
-            .filter(|&&date| token_2_data.contains_key(&date))
-            .copied()
-            .collect();
+        let token_1_values = Self::apply_options(token_1_prices, &options)?;
+        let token_2_values = Self::apply_options(token_2_prices, &options)?;
+        let observations = token_1_values.len();
+
+        let (covariance, correlation_coefficient) =
+            Self::calculate_correlation_estimate(&token_1_values, &token_2_values, &options)?;
+        let significance = Self::calculate_significance(
+            correlation_coefficient,
+            observations,
+            options.method,
+            options.confidence,
+        );
 
-        if common_dates.is_empty() {
+        let ewma = match options.half_life {
+            Some(half_life) => {
+                let weights = Self::ewma_weights(token_1_values.len(), half_life);
+                let (covariance, correlation_coefficient) =
+                    Self::calculate_weighted_covariance_and_correlation(
+                        &token_1_values,
+                        &token_2_values,
+                        &weights,
+                    )?;
+
+                Some(EwmaCovariance {
+                    half_life,
+                    lambda: Self::ewma_lambda(half_life),
+                    covariance,
+                    correlation_coefficient,
+                })
+            }
+            None => None,
+        };
+
+        let annualization_factor =
+            interval.annualization_factor(self.alignment.calendar(&calendars));
+
+        Ok(HistoricalDataCovariance {
+            covariance,
+            correlation_coefficient,
+            significance,
+            token_1,
+            token_2,
+            range,
+            source: self.source(),
+            interval,
+            alignment: self.alignment,
+            basis: options.basis,
+            annualization_factor,
+            estimation: options.estimation,
+            method: options.method,
+            winsorize: options.winsorize,
+            observations,
+            ewma,
+            data_quality: Self::data_quality(token_data),
+        })
+    }
+
+    /// Calculates the covariance and correlation matrices of several tokens based on historical data.
+    ///
+    /// The data of all tokens is fetched concurrently and aligned with the
+    /// alignment policy of the service before the basis is applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The tokens to include in the matrices.
+    /// * `range` - The time range of historical data to use.
+    /// * `interval` - The bar interval of historical data to use.
+    /// * `options` - The basis, estimation and half-life of the estimates.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HistoricalDataMatrix, anyhow::Error>` - Result containing the calculated matrices, or an error.
+    pub async fn calculate_matrix(
+        &self,
+        tokens: Vec<Token>,
+        range: DateRange,
+        interval: Interval,
+        options: CorrelationOptions,
+    ) -> Result<HistoricalDataMatrix, anyhow::Error> {
+        if tokens.len() < 2 {
             return Err(anyhow!(
-                "No common timestamps found between the two tokens."
+                "At least two tokens are required to build a matrix."
             ));
         }
 
-        if token_1_data.len() != token_2_data.len() {
+        let token_data = try_join_all(
+            tokens
+                .iter()
+                .map(|token| self.get_data_by_token(token, &range, interval)),
+        )
+        .await?;
+
+        let calendars: Vec<TradingCalendar> = tokens.iter().map(Token::calendar).collect();
+        let (_, aligned) = self.align_series(&token_data, &calendars, interval);
+
+        if aligned[0].is_empty() {
+            return Err(InsufficientData(
+                "No common timestamps found between the tokens.".to_string(),
+            )
+            .into());
+        }
+
+        let values = aligned
+            .iter()
+            .map(|prices| Self::apply_options(prices, &options))
+            .collect::<Result<Vec<Vec<f64>>, anyhow::Error>>()?;
+
+        let (covariance, correlation) = Self::calculate_matrices(&values, |v1, v2| {
+            Self::calculate_correlation_estimate(v1, v2, &options)
+        })?;
+
+        let ewma = match options.half_life {
+            Some(half_life) => {
+                let weights = Self::ewma_weights(values[0].len(), half_life);
+                let (covariance, correlation) = Self::calculate_matrices(&values, |v1, v2| {
+                    Self::calculate_weighted_covariance_and_correlation(v1, v2, &weights)
+                })?;
+
+                Some(EwmaMatrix {
+                    half_life,
+                    lambda: Self::ewma_lambda(half_life),
+                    covariance,
+                    correlation,
+                })
+            }
+            None => None,
+        };
+
+        Ok(HistoricalDataMatrix {
+            observations: values[0].len(),
+            annualization_factor: interval
+                .annualization_factor(self.alignment.calendar(&calendars)),
+            tokens,
+            range,
+            source: self.source(),
+            interval,
+            alignment: self.alignment,
+            basis: options.basis,
+            estimation: options.estimation,
+            method: options.method,
+            winsorize: options.winsorize,
+            covariance,
+            correlation,
+            ewma,
+            data_quality: Self::data_quality(token_data),
+        })
+    }
+
+    /// Calculates the beta and alpha of a token against another based on historical data.
+    ///
+    /// The returns of `token_1` are regressed on the returns of `token_2` with
+    /// ordinary least squares, over their aligned bars.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_1` - The token whose returns are explained.
+    /// * `token_2` - The token whose returns explain them, typically an index.
+    /// * `range` - The time range of historical data to use.
+    /// * `interval` - The bar interval of historical data to use.
+    /// * `basis` - The returns the regression is fitted on.
+    /// * `window` - The rolling window of the beta series, `None` to skip it.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HistoricalDataBeta, anyhow::Error>` - Result containing the regression statistics, or an error.
+    pub async fn calculate_beta(
+        &self,
+        token_1: Token,
+        token_2: Token,
+        range: DateRange,
+        interval: Interval,
+        basis: Basis,
+        window: Option<RollingWindow>,
+    ) -> Result<HistoricalDataBeta, anyhow::Error> {
+        if basis == Basis::Price {
+            return Err(anyhow!("Beta is estimated on returns, not prices."));
+        }
+
+        let (token_1_data, token_2_data) = tokio::try_join!(
+            self.get_data_by_token(&token_1, &range, interval),
+            self.get_data_by_token(&token_2, &range, interval),
+        )?;
+
+        let token_data = [token_1_data, token_2_data];
+        let calendars = [token_1.calendar(), token_2.calendar()];
+        let (dates, aligned) = self.align_series(&token_data, &calendars, interval);
+
+        if aligned[0].is_empty() {
+            return Err(InsufficientData(
+                "No common timestamps found between the two tokens.".to_string(),
+            )
+            .into());
+        }
+
+        let token_1_values = Self::apply_basis(&aligned[0], basis)?;
+        let token_2_values = Self::apply_basis(&aligned[1], basis)?;
+        let regression = Regression::fit(&token_1_values, &token_2_values)?;
+
+        let rolling = match window {
+            Some(window) => Some(RollingBeta {
+                window,
+                series: Self::calculate_rolling(&dates, token_1_values.len(), window, |r| {
+                    // A window over a constant explanatory series has no beta, not a failed request
+                    Ok(
+                        Regression::fit(&token_1_values[r.clone()], &token_2_values[r])
+                            .map_or(f64::NAN, |regression| regression.beta),
+                    )
+                })?,
+            }),
+            None => None,
+        };
+
+        let annualization_factor =
+            interval.annualization_factor(self.alignment.calendar(&calendars));
+
+        Ok(HistoricalDataBeta {
+            token_1,
+            token_2,
+            range,
+            source: self.source(),
+            interval,
+            alignment: self.alignment,
+            basis,
+            annualization_factor,
+            observations: regression.observations,
+            beta: regression.beta,
+            beta_standard_error: regression.beta_standard_error,
+            alpha: regression.alpha * annualization_factor,
+            alpha_standard_error: regression.alpha_standard_error * annualization_factor,
+            r_squared: regression.r_squared,
+            residual_volatility: (regression.residual_variance * annualization_factor).sqrt(),
+            rolling,
+            data_quality: Self::data_quality(token_data),
+        })
+    }
+
+    /// Calculates the cross-correlation and Granger causality between two tokens based on historical data.
+    ///
+    /// Lags count aligned bars rather than calendar days, so under the default
+    /// alignment a lag of one bar spans a weekend between Friday and Monday.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_1` - First token.
+    /// * `token_2` - Second token.
+    /// * `range` - The time range of historical data to use.
+    /// * `interval` - The bar interval of historical data to use.
+    /// * `basis` - The returns the statistics are computed on.
+    /// * `lags` - The largest lag of the cross-correlation and the lags of the Granger-causality models.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HistoricalDataCrossCorrelation, anyhow::Error>` - Result containing the correlation at each lag and the causality tests, or an error.
+    pub async fn calculate_cross_correlation(
+        &self,
+        token_1: Token,
+        token_2: Token,
+        range: DateRange,
+        interval: Interval,
+        basis: Basis,
+        lags: CrossCorrelationLags,
+    ) -> Result<HistoricalDataCrossCorrelation, anyhow::Error> {
+        if basis == Basis::Price {
             return Err(anyhow!(
-                "The historical data amount from token_1<{}> is not equal to token_2<{}>.",
-                token_1.as_string(),
-                token_2.as_string()
+                "Cross-correlation is estimated on returns, not prices."
             ));
         }
 
-        let mean1 = token_1_data.values().sum::<f64>() / token_1_data.len() as f64;
-        let mean2 = token_2_data.values().sum::<f64>() / token_2_data.len() as f64;
+        let (token_1_data, token_2_data) = tokio::try_join!(
+            self.get_data_by_token(&token_1, &range, interval),
+            self.get_data_by_token(&token_2, &range, interval),
+        )?;
 
-        let covariance = common_dates
-            .iter()
-            .map(|date| {
-                let deviation1 = token_1_data[date] - mean1;
-                let deviation2 = token_2_data[date] - mean2;
-                deviation1 * deviation2
-            })
-            .sum::<f64>()
-            / common_dates.len() as f64;
+        let token_data = [token_1_data, token_2_data];
+        let calendars = [token_1.calendar(), token_2.calendar()];
+        let (_, aligned) = self.align_series(&token_data, &calendars, interval);
+        let token_1_values = Self::apply_basis(&aligned[0], basis)?;
+        let token_2_values = Self::apply_basis(&aligned[1], basis)?;
+        let observations = token_1_values.len();
+        let CrossCorrelationLags {
+            max_lag,
+            granger_lags,
+        } = lags;
 
-        // Compute standard deviations
-        let std_dev1 = (common_dates
-            .iter()
-            .map(|date| {
-                let deviation1 = token_1_data[date] - mean1;
-                deviation1 * deviation1
+        // Every lag keeps at least two overlapping pairs
+        if observations < max_lag + 2 {
+            return Err(InsufficientData(format!(
+                "Not enough common values <{}> for lags up to {}.",
+                observations, max_lag
+            ))
+            .into());
+        }
+
+        let correlations = (-(max_lag as i64)..=max_lag as i64)
+            .map(|lag| {
+                let shift = lag.unsigned_abs() as usize;
+                let (leading, lagging) = if lag >= 0 {
+                    (&token_1_values, &token_2_values)
+                } else {
+                    (&token_2_values, &token_1_values)
+                };
+
+                let (_, correlation) = Self::calculate_covariance_and_correlation(
+                    &leading[..observations - shift],
+                    &lagging[shift..],
+                    Estimation::Population,
+                )?;
+
+                Ok(LagCorrelation {
+                    lag,
+                    observations: observations - shift,
+                    correlation,
+                })
             })
-            .sum::<f64>()
-            / common_dates.len() as f64)
-            .sqrt();
+            .collect::<Result<Vec<LagCorrelation>, anyhow::Error>>()?;
 
-        let std_dev2 = (common_dates
+        let peak_lag = correlations
             .iter()
-            .map(|date| {
-                let deviation2 = token_2_data[date] - mean2;
-                deviation2 * deviation2
-            })
-            .sum::<f64>()
-            / common_dates.len() as f64)
-            .sqrt();
+            .filter(|point| point.correlation.is_finite())
+            .max_by(|a, b| a.correlation.abs().total_cmp(&b.correlation.abs()))
+            .map(|point| point.lag);
 
-        // Compute correlation coefficient
-        let correlation_coefficient = covariance / (std_dev1 * std_dev2);
+        let granger = GrangerCausality {
+            token_1_causes_token_2: GrangerTest::run(
+                &token_2_values,
+                &token_1_values,
+                granger_lags,
+            )?,
+            token_2_causes_token_1: GrangerTest::run(
+                &token_1_values,
+                &token_2_values,
+                granger_lags,
+            )?,
+        };
 
-        Ok(HistoricalDataCovariance {
-            covariance,
-            correlation_coefficient,
+        Ok(HistoricalDataCrossCorrelation {
             token_1,
             token_2,
+            range,
+            source: self.source(),
+            interval,
+            alignment: self.alignment,
+            basis,
+            observations,
+            lags,
+            correlations,
+            peak_lag,
+            granger,
+            data_quality: Self::data_quality(token_data),
+        })
+    }
+
+    /// Calculates the rolling correlation between two tokens based on historical data.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_1` - First token.
+    /// * `token_2` - Second token.
+    /// * `range` - The time range of historical data to use.
+    /// * `interval` - The bar interval of historical data to use.
+    /// * `basis` - The series (prices or returns) the statistics are computed on.
+    /// * `window` - The number of bars in each rolling window and the step between them.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HistoricalDataRolling, anyhow::Error>` - Result containing the dated correlation series, or an error.
+    pub async fn calculate_rolling_correlation(
+        &self,
+        token_1: Token,
+        token_2: Token,
+        range: DateRange,
+        interval: Interval,
+        basis: Basis,
+        window: RollingWindow,
+    ) -> Result<HistoricalDataRolling, anyhow::Error> {
+        let (token_1_data, token_2_data) = tokio::try_join!(
+            self.get_data_by_token(&token_1, &range, interval),
+            self.get_data_by_token(&token_2, &range, interval),
+        )?;
+
+        let token_data = [token_1_data, token_2_data];
+        let calendars = [token_1.calendar(), token_2.calendar()];
+        let (dates, aligned) = self.align_series(&token_data, &calendars, interval);
+        let token_1_values = Self::apply_basis(&aligned[0], basis)?;
+        let token_2_values = Self::apply_basis(&aligned[1], basis)?;
+
+        let series = Self::calculate_rolling(&dates, token_1_values.len(), window, |r| {
+            let (_, correlation) = Self::calculate_covariance_and_correlation(
+                &token_1_values[r.clone()],
+                &token_2_values[r],
+                Estimation::Population,
+            )?;
+            Ok(correlation)
+        })?;
+
+        let annualization_factor =
+            interval.annualization_factor(self.alignment.calendar(&calendars));
+
+        Ok(HistoricalDataRolling {
+            tokens: vec![token_1, token_2],
+            annualization_factor,
+            range,
+            source: self.source(),
+            interval,
+            alignment: Some(self.alignment),
+            basis,
+            window,
+            series,
+            data_quality: Self::data_quality(token_data),
+        })
+    }
+
+    /// Calculates the rolling realized volatility of a token based on historical data.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token for which to calculate realized volatility.
+    /// * `range` - The time range of historical data to use.
+    /// * `interval` - The bar interval of historical data to use.
+    /// * `window` - The number of log returns in each rolling window and the step between them.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HistoricalDataRolling, anyhow::Error>` - Result containing the dated annualized volatility series, or an error.
+    pub async fn calculate_rolling_volatility(
+        &self,
+        token: Token,
+        range: DateRange,
+        interval: Interval,
+        window: RollingWindow,
+    ) -> Result<HistoricalDataRolling, anyhow::Error> {
+        let price_data = self.get_data_by_token(&token, &range, interval).await?;
+
+        let token_data = [price_data];
+        let (dates, aligned) = self.align_series(&token_data, &[token.calendar()], interval);
+        let log_returns = Self::calculate_log_returns(&aligned[0])?;
+        let annualization_factor = interval.annualization_factor(token.calendar());
+
+        let series = Self::calculate_rolling(&dates, log_returns.len(), window, |r| {
+            Self::calculate_standard_deviation(
+                &log_returns[r],
+                annualization_factor,
+                Estimation::Population,
+            )
+        })?;
+
+        Ok(HistoricalDataRolling {
+            tokens: vec![token],
+            range,
+            source: self.source(),
+            interval,
+            alignment: None,
+            basis: Basis::LogReturn,
+            annualization_factor,
+            window,
+            series,
+            data_quality: Self::data_quality(token_data),
         })
     }
 
@@ -175,88 +945,715 @@ impl HistoricalData {
     /// # Arguments
     ///
     /// * `token` - The token for which to calculate realized volatility.
+    /// * `range` - The time range of historical data to use.
+    /// * `interval` - The bar interval of historical data to use.
+    /// * `estimators` - The range-based estimators to report next to the close-to-close one.
+    /// * `estimation` - The denominator of the close-to-close variance.
     ///
     /// # Returns
     ///
-    /// * `Result<f64, anyhow::Error>` - Result containing the realized volatility or an error.
-    pub async fn calculate_realized_volatility(token: Token) -> Result<f64, anyhow::Error> {
-        let price_data = Self::get_yearly_data_by_token(&token).await?;
+    /// * `Result<HistoricalDataVolatility, anyhow::Error>` - Result containing the realized volatility or an error.
+    pub async fn calculate_realized_volatility(
+        &self,
+        token: Token,
+        range: DateRange,
+        interval: Interval,
+        estimators: &[Estimator],
+        estimation: Estimation,
+    ) -> Result<HistoricalDataVolatility, anyhow::Error> {
+        let price_data = self.get_data_by_token(&token, &range, interval).await?;
 
         if price_data.is_empty() {
-            return Err(anyhow!("No price data available for the specified token."));
+            return Err(InsufficientData(format!(
+                "No price data available for token<{}>.",
+                token.as_string()
+            ))
+            .into());
         }
 
-        let mut sorted_dates: Vec<_> = price_data.keys().collect();
-        sorted_dates.sort(); // Ensure data is in chronological order
-
-        let prices: Vec<f64> = sorted_dates.iter().map(|date| price_data[date]).collect();
+        let prices: Vec<f64> = price_data.closes(); // Bars are in chronological order
         let log_returns: Vec<f64> = Self::calculate_log_returns(&prices)?;
-        let realized_volatility = Self::calculate_standard_deviation(&log_returns)?;
+        let annualization_factor = interval.annualization_factor(token.calendar());
+        let annualized_volatility =
+            Self::calculate_standard_deviation(&log_returns, annualization_factor, estimation)?;
+
+        // Daily figures are scaled from the annualized ones, whatever the bar interval
+        let daily_factor = Interval::Daily.annualization_factor(token.calendar());
+
+        let bars: Vec<Bar> = price_data.bars.values().copied().collect();
+        let estimators = estimators
+            .iter()
+            .map(|estimator| {
+                let (variance, observations) = estimator.bar_variance(&bars)?;
+                let annualized_volatility = (variance * annualization_factor).sqrt();
+
+                Ok(VolatilityEstimate {
+                    estimator: *estimator,
+                    observations,
+                    daily_volatility: annualized_volatility / daily_factor.sqrt(),
+                    annualized_volatility,
+                })
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+        Ok(HistoricalDataVolatility {
+            symbol: token.id().to_string(),
+            token,
+            range,
+            source: self.source(),
+            interval,
+            annualization_factor,
+            window: SampleWindow {
+                start: bars[0].date,
+                end: bars[bars.len() - 1].date,
+            },
+            estimation,
+            observations: log_returns.len(),
+            daily_volatility: annualized_volatility / daily_factor.sqrt(),
+            annualized_volatility,
+            estimators,
+            data_quality: price_data.quality,
+        })
+    }
 
-        Ok(realized_volatility)
+    /// Fits a conditional volatility model on the log returns of a token and forecasts its volatility.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token for which to forecast volatility.
+    /// * `range` - The time range of historical data to fit the model on.
+    /// * `interval` - The bar interval of historical data to use.
+    /// * `model` - The volatility model to fit.
+    /// * `horizon` - The number of bars to forecast.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HistoricalDataForecast, anyhow::Error>` - Result containing the fitted model and its forecast, or an error.
+    pub async fn calculate_volatility_forecast(
+        &self,
+        token: Token,
+        range: DateRange,
+        interval: Interval,
+        model: ForecastModel,
+        horizon: usize,
+    ) -> Result<HistoricalDataForecast, anyhow::Error> {
+        let price_data = self.get_data_by_token(&token, &range, interval).await?;
+
+        let token_data = [price_data];
+        let (dates, aligned) = self.align_series(&token_data, &[token.calendar()], interval);
+        let log_returns = Self::calculate_log_returns(&aligned[0])?;
+        let annualization_factor = interval.annualization_factor(token.calendar());
+
+        let fit = model.fit(&log_returns)?;
+
+        // Returns are dated by the bar they end on
+        let conditional_volatility = dates[1..]
+            .iter()
+            .zip(&fit.variances)
+            .map(|(date, variance)| RollingPoint {
+                date: *date,
+                value: (variance * annualization_factor).sqrt(),
+            })
+            .collect();
+
+        let mut cumulative_variance = 0.0;
+        let forecast = fit
+            .model
+            .forecast(fit.next_variance, horizon)
+            .into_iter()
+            .enumerate()
+            .map(|(i, variance)| {
+                cumulative_variance += variance;
+                ForecastPoint {
+                    horizon: i + 1,
+                    volatility: (variance * annualization_factor).sqrt(),
+                    cumulative_volatility: cumulative_variance.sqrt(),
+                }
+            })
+            .collect();
+
+        let [price_data] = token_data;
+
+        Ok(HistoricalDataForecast {
+            token,
+            range,
+            source: self.source(),
+            interval,
+            annualization_factor,
+            observations: log_returns.len(),
+            parameters: fit.model,
+            conditional_volatility,
+            forecast,
+            data_quality: price_data.quality,
+        })
     }
 
-    /// Fetches the yearly historical data for a given token from Yahoo Finance API.
+    /// Returns historical data for a given token and range, served from the price cache when possible.
+    ///
+    /// Only the part of the range missing from the cache is fetched, after
+    /// which the cache is updated with the fetched bars. The cache keeps bars
+    /// as the provider dates them, daily crypto bars being moved to their
+    /// as-of close afterwards.
     ///
     /// # Arguments
     ///
-    /// * `token` - The token for which to fetch the historical data.
+    /// * `token` - The token for which to get the historical data.
+    /// * `range` - The time range of historical data to get.
+    /// * `interval` - The bar interval of historical data to get.
     ///
     /// # Returns
     ///
-    /// * `Result<HashMap<NaiveDate, f64>, anyhow::Error>` - Result containing the historical data or an error.
-    pub async fn get_yearly_data_by_token(
+    /// * `Result<PriceSeries, anyhow::Error>` - Result containing the historical bars keyed by bar start, or an error.
+    pub async fn get_data_by_token(
+        &self,
         token: &Token,
-    ) -> Result<HashMap<NaiveDate, f64>, anyhow::Error> {
-        let method = Method::GET;
-        let headers = Self::build_headers();
-        let one_year_ago = Self::get_year_ago_date();
-        let url = Self::build_url(&token, &one_year_ago);
-        let res = Request::process_request(method, url, Some(headers), None).await?;
-
-        if let Some(data) = res["chart"]["result"][0]["indicators"]["quote"][0]["close"].as_array()
-        {
-            let filtered_data: Vec<f64> = data
-                .into_iter()
-                .filter_map(|v| v.as_f64()) // Filters out `null` and converts `Value` to `f64`
-                .collect();
-
-            let timestamp: Vec<NaiveDate> = res["chart"]["result"][0]["timestamp"]
-                .as_array()
-                .unwrap()
-                .clone()
-                .into_iter()
-                .map(|v| {
-                    DateTime::from_timestamp(v.as_i64().unwrap(), 0)
-                        .unwrap()
-                        .date_naive()
-                })
-                .collect();
+        range: &DateRange,
+        interval: Interval,
+    ) -> Result<PriceSeries, anyhow::Error> {
+        let series = self.get_source_data(token, range, interval).await?;
 
-            let mut final_hashset: HashMap<NaiveDate, f64> = HashMap::new();
-            for (i, v) in filtered_data.iter().enumerate() {
-                final_hashset.insert(timestamp[i], v.clone());
+        Ok(match self.crypto_close {
+            Some(close)
+                if interval == Interval::Daily && token.calendar() == TradingCalendar::Crypto =>
+            {
+                series.redated(token, interval, |date| close.daily_bar_start(date))
             }
+            _ => series,
+        })
+    }
 
-            Ok(final_hashset)
-        } else {
-            Err(anyhow!(
-                "Not possible to fetch yearly token<{}> data.",
-                token.as_string()
+    /// Returns historical data for a given token and range as dated by the source.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token for which to get the historical data.
+    /// * `range` - The time range of historical data to get.
+    /// * `interval` - The bar interval of historical data to get.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PriceSeries, anyhow::Error>` - Result containing the historical bars keyed by bar start, or an error.
+    async fn get_source_data(
+        &self,
+        token: &Token,
+        range: &DateRange,
+        interval: Interval,
+    ) -> Result<PriceSeries, anyhow::Error> {
+        let source = self.source.kind();
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => {
+                return self
+                    .source
+                    .fetch(token, range, interval)
+                    .await
+                    .map_err(|error| Self::fetch_error(token, source, error))
+            }
+        };
+
+        let cached = token.clone();
+        let entry =
+            PriceCache::blocking(cache, move |cache| cache.entry(source, &cached, interval))
+                .await?;
+
+        if let Some(missing) = cache.missing_range(entry.as_ref(), range) {
+            debug!(
+                "Fetching token<{}> {} bars from {} between {} and {}.",
+                token.id(),
+                interval.id(),
+                source.id(),
+                missing.start,
+                missing.end
+            );
+
+            let fetched = self
+                .source
+                .fetch(token, &missing, interval)
+                .await
+                .map_err(|error| Self::fetch_error(token, source, error))?;
+            let bars: Vec<Bar> = fetched.bars.values().copied().collect();
+            let cached = token.clone();
+            PriceCache::blocking(cache, move |cache| {
+                cache.store(source, &cached, interval, &missing, &bars)
+            })
+            .await?;
+
+            // A full fetch keeps the provider's own data-quality report
+            if missing == *range {
+                return Ok(fetched);
+            }
+        }
+
+        let (cached, range) = (token.clone(), *range);
+        let bars = PriceCache::blocking(cache, move |cache| {
+            cache.bars(source, &cached, interval, &range)
+        })
+        .await?;
+
+        Ok(PriceSeries::from_raw_bars(
+            token,
+            interval,
+            bars.into_iter().map(Some).collect(),
+        ))
+    }
+
+    /// Wraps the error of a failed fetch with the token and source it was fetched from.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token being fetched.
+    /// * `source` - The source being fetched from.
+    /// * `error` - The error of the fetch.
+    ///
+    /// # Returns
+    ///
+    /// * `anyhow::Error` - The wrapped error.
+    fn fetch_error(token: &Token, source: SourceKind, error: anyhow::Error) -> anyhow::Error {
+        FetchError {
+            symbol: token.id().to_string(),
+            provider: source,
+            error,
+        }
+        .into()
+    }
+
+    /// Aligns price series with the alignment policy of the service.
+    ///
+    /// # Arguments
+    ///
+    /// * `series` - Bars of each token keyed by bar start.
+    /// * `calendars` - The trading calendar of each token.
+    /// * `interval` - The bar interval of the series.
+    ///
+    /// # Returns
+    ///
+    /// * `(Vec<NaiveDateTime>, Vec<Vec<f64>>)` - The aligned bars and the closing prices of each token on them, in chronological order.
+    fn align_series(
+        &self,
+        series: &[PriceSeries],
+        calendars: &[TradingCalendar],
+        interval: Interval,
+    ) -> (Vec<NaiveDateTime>, Vec<Vec<f64>>) {
+        self.alignment.align(series, calendars, interval)
+    }
+
+    /// Extracts the data-quality reports of fetched series.
+    ///
+    /// # Arguments
+    ///
+    /// * `series` - The fetched series, in token order.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<DataQuality>` - The data-quality report of each series.
+    fn data_quality<I: IntoIterator<Item = PriceSeries>>(series: I) -> Vec<DataQuality> {
+        series.into_iter().map(|data| data.quality).collect()
+    }
+
+    /// Applies a statistic over a rolling window of an aligned series.
+    ///
+    /// # Arguments
+    ///
+    /// * `dates` - The bar dates of the series, aligned with its last values.
+    /// * `len` - The number of values in the series.
+    /// * `window` - The number of values in each window and the step between them.
+    /// * `statistic` - Computes the statistic over the value indices of a window.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<RollingPoint>, anyhow::Error>` - The statistic dated by the last bar of each window, or an error.
+    fn calculate_rolling<F>(
+        dates: &[NaiveDateTime],
+        len: usize,
+        window: RollingWindow,
+        statistic: F,
+    ) -> Result<Vec<RollingPoint>, anyhow::Error>
+    where
+        F: Fn(std::ops::Range<usize>) -> Result<f64, anyhow::Error>,
+    {
+        let RollingWindow { window, step } = window;
+
+        if window < 2 || step == 0 {
+            return Err(anyhow!(
+                "The rolling window must be at least 2 and the step at least 1."
+            ));
+        }
+
+        if len < window {
+            return Err(InsufficientData(format!(
+                "Not enough data points <{}> for a rolling window of <{}>.",
+                len, window
             ))
+            .into());
         }
+
+        // Returns are dated by the bar they end on, so drop the leading dates
+        let dates = &dates[dates.len() - len..];
+
+        (window..=len)
+            .step_by(step)
+            .map(|end| {
+                Ok(RollingPoint {
+                    date: dates[end - 1],
+                    value: statistic(end - window..end)?,
+                })
+            })
+            .collect()
     }
 
-    /// Returns the date from one year ago.
+    /// Transforms a chronological price series into the requested basis.
+    ///
+    /// # Arguments
+    ///
+    /// * `prices` - A slice of f64 representing the prices.
+    /// * `basis` - The basis to transform the prices into.
     ///
     /// # Returns
     ///
-    /// * `DateTime<Local>` - The date and time from one year ago.
-    fn get_year_ago_date() -> DateTime<Local> {
-        let now = Local::now();
-        let one_year_ago = now - Duration::days(365);
+    /// * `Result<Vec<f64>, anyhow::Error>` - Result containing the transformed series or an error.
+    fn apply_basis(prices: &[f64], basis: Basis) -> Result<Vec<f64>, anyhow::Error> {
+        match basis {
+            Basis::Price => Ok(prices.to_vec()),
+            Basis::SimpleReturn => Self::calculate_simple_returns(prices),
+            Basis::LogReturn => Self::calculate_log_returns(prices),
+        }
+    }
 
-        one_year_ago
+    /// Applies the basis of the options to a series of prices, then winsorizes it if requested.
+    ///
+    /// # Arguments
+    ///
+    /// * `prices` - The closing prices in chronological order.
+    /// * `options` - The basis and winsorizing fraction.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<f64>, anyhow::Error>` - The values the estimates are computed on, or an error.
+    fn apply_options(
+        prices: &[f64],
+        options: &CorrelationOptions,
+    ) -> Result<Vec<f64>, anyhow::Error> {
+        let values = Self::apply_basis(prices, options.basis)?;
+
+        Ok(match options.winsorize {
+            Some(fraction) => CorrelationMethod::winsorize(&values, fraction),
+            None => values,
+        })
+    }
+
+    /// Calculates the covariance and correlation coefficient of two aligned series.
+    ///
+    /// # Arguments
+    ///
+    /// * `values_1` - The first series.
+    /// * `values_2` - The second series, aligned with the first one.
+    /// * `estimation` - The denominator of the covariance and standard deviations.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(f64, f64), anyhow::Error>` - Result containing the covariance and correlation coefficient, or an error.
+    fn calculate_covariance_and_correlation(
+        values_1: &[f64],
+        values_2: &[f64],
+        estimation: Estimation,
+    ) -> Result<(f64, f64), anyhow::Error> {
+        if values_1.len() != values_2.len() {
+            return Err(anyhow!(
+                "The series lengths <{}> and <{}> are not equal.",
+                values_1.len(),
+                values_2.len()
+            ));
+        }
+
+        if values_1.is_empty() {
+            return Err(InsufficientData(
+                "No values available to calculate covariance.".to_string(),
+            )
+            .into());
+        }
+
+        if values_1.len() < estimation.min_len() {
+            return Err(InsufficientData(format!(
+                "Not enough values <{}> to calculate a sample covariance.",
+                values_1.len()
+            ))
+            .into());
+        }
+
+        let len = values_1.len() as f64;
+        let denominator = estimation.denominator(values_1.len());
+        let mean1 = values_1.iter().sum::<f64>() / len;
+        let mean2 = values_2.iter().sum::<f64>() / len;
+
+        let covariance = values_1
+            .iter()
+            .zip(values_2)
+            .map(|(v1, v2)| (v1 - mean1) * (v2 - mean2))
+            .sum::<f64>()
+            / denominator;
+
+        // Compute standard deviations
+        let std_dev1 =
+            (values_1.iter().map(|v| (v - mean1).powi(2)).sum::<f64>() / denominator).sqrt();
+        let std_dev2 =
+            (values_2.iter().map(|v| (v - mean2).powi(2)).sum::<f64>() / denominator).sqrt();
+
+        // Compute correlation coefficient
+        let correlation_coefficient = covariance / (std_dev1 * std_dev2);
+
+        Ok((covariance, correlation_coefficient))
+    }
+
+    /// Tests a correlation coefficient against the null hypothesis of no correlation.
+    ///
+    /// Pearson and Spearman coefficients are tested with the t-statistic
+    /// `r * sqrt((n - 2) / (1 - r^2))` and `n - 2` degrees of freedom, Kendall
+    /// ones with the normal approximation of tau. The confidence interval is
+    /// built on the Fisher transform `atanh(r)`, which is close to normal.
+    ///
+    /// # Arguments
+    ///
+    /// * `correlation` - The correlation coefficient.
+    /// * `observations` - The number of observations it was computed on.
+    /// * `method` - The method the coefficient was computed with.
+    /// * `confidence` - The confidence level of the interval.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<CorrelationSignificance>` - The significance, `None` with too few observations or a perfect correlation.
+    fn calculate_significance(
+        correlation: f64,
+        observations: usize,
+        method: CorrelationMethod,
+        confidence: f64,
+    ) -> Option<CorrelationSignificance> {
+        if observations < method.min_fisher_observations()
+            || !correlation.is_finite()
+            || correlation.abs() >= 1.0
+        {
+            return None;
+        }
+
+        let n = observations as f64;
+        let normal = Normal::standard();
+
+        let (test, statistic, degrees_of_freedom, p_value) = match method {
+            CorrelationMethod::Pearson | CorrelationMethod::Spearman => {
+                let degrees_of_freedom = n - 2.0;
+                let statistic =
+                    correlation * (degrees_of_freedom / (1.0 - correlation.powi(2))).sqrt();
+                let p_value = 2.0
+                    * StudentsT::new(0.0, 1.0, degrees_of_freedom)
+                        .ok()?
+                        .sf(statistic.abs());
+
+                (
+                    SignificanceTest::StudentT,
+                    statistic,
+                    Some(degrees_of_freedom),
+                    p_value,
+                )
+            }
+            CorrelationMethod::Kendall => {
+                let statistic =
+                    3.0 * correlation * (n * (n - 1.0)).sqrt() / (2.0 * (2.0 * n + 5.0)).sqrt();
+                let p_value = 2.0 * normal.sf(statistic.abs());
+
+                (SignificanceTest::Normal, statistic, None, p_value)
+            }
+        };
+
+        let z = correlation.atanh();
+        let margin = normal.inverse_cdf((1.0 + confidence) / 2.0)
+            * method.fisher_variance(observations).sqrt();
+
+        Some(CorrelationSignificance {
+            test,
+            statistic,
+            degrees_of_freedom,
+            p_value,
+            confidence_level: confidence,
+            confidence_interval: [(z - margin).tanh(), (z + margin).tanh()],
+        })
+    }
+
+    /// Calculates the covariance and the correlation coefficient of two aligned series with a method.
+    ///
+    /// # Arguments
+    ///
+    /// * `values_1` - The first series.
+    /// * `values_2` - The second series, aligned with the first one.
+    /// * `options` - The estimation and correlation method.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(f64, f64), anyhow::Error>` - Result containing the covariance and correlation coefficient, or an error.
+    fn calculate_correlation_estimate(
+        values_1: &[f64],
+        values_2: &[f64],
+        options: &CorrelationOptions,
+    ) -> Result<(f64, f64), anyhow::Error> {
+        let (covariance, pearson) =
+            Self::calculate_covariance_and_correlation(values_1, values_2, options.estimation)?;
+
+        let correlation = match options.method {
+            CorrelationMethod::Pearson => pearson,
+            CorrelationMethod::Spearman => {
+                let (_, spearman) = Self::calculate_covariance_and_correlation(
+                    &CorrelationMethod::ranks(values_1),
+                    &CorrelationMethod::ranks(values_2),
+                    options.estimation,
+                )?;
+                spearman
+            }
+            CorrelationMethod::Kendall => CorrelationMethod::kendall_tau_b(values_1, values_2),
+        };
+
+        Ok((covariance, correlation))
+    }
+
+    /// Calculates the exponentially-weighted covariance and correlation coefficient of two aligned series.
+    ///
+    /// # Arguments
+    ///
+    /// * `values_1` - The first series.
+    /// * `values_2` - The second series, aligned with the first one.
+    /// * `weights` - The weight of each value, summing to one.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(f64, f64), anyhow::Error>` - Result containing the covariance and correlation coefficient, or an error.
+    fn calculate_weighted_covariance_and_correlation(
+        values_1: &[f64],
+        values_2: &[f64],
+        weights: &[f64],
+    ) -> Result<(f64, f64), anyhow::Error> {
+        if values_1.len() != values_2.len() || values_1.len() != weights.len() {
+            return Err(anyhow!(
+                "The series lengths <{}>, <{}> and weights <{}> are not equal.",
+                values_1.len(),
+                values_2.len(),
+                weights.len()
+            ));
+        }
+
+        if values_1.is_empty() {
+            return Err(InsufficientData(
+                "No values available to calculate covariance.".to_string(),
+            )
+            .into());
+        }
+
+        let mean1 = values_1
+            .iter()
+            .zip(weights)
+            .map(|(v, w)| v * w)
+            .sum::<f64>();
+        let mean2 = values_2
+            .iter()
+            .zip(weights)
+            .map(|(v, w)| v * w)
+            .sum::<f64>();
+
+        let (mut covariance, mut variance1, mut variance2) = (0.0, 0.0, 0.0);
+        for ((v1, v2), w) in values_1.iter().zip(values_2).zip(weights) {
+            let (d1, d2) = (v1 - mean1, v2 - mean2);
+            covariance += w * d1 * d2;
+            variance1 += w * d1 * d1;
+            variance2 += w * d2 * d2;
+        }
+
+        let correlation_coefficient = covariance / (variance1 * variance2).sqrt();
+
+        Ok((covariance, correlation_coefficient))
+    }
+
+    /// Returns the decay factor of an exponentially-weighted average with a half-life in bars.
+    fn ewma_lambda(half_life: f64) -> f64 {
+        0.5f64.powf(1.0 / half_life)
+    }
+
+    /// Calculates the exponential weights of a series, the latest value weighing the most.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The number of values in the series.
+    /// * `half_life` - The number of bars after which a weight halves.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<f64>` - The weight of each value in chronological order, summing to one.
+    fn ewma_weights(len: usize, half_life: f64) -> Vec<f64> {
+        let lambda = Self::ewma_lambda(half_life);
+        let weights: Vec<f64> = (0..len)
+            .map(|i| lambda.powi((len - 1 - i) as i32))
+            .collect();
+        let total = weights.iter().sum::<f64>();
+
+        weights.into_iter().map(|w| w / total).collect()
+    }
+
+    /// Builds the covariance and correlation matrices of several aligned series.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The aligned series of each token.
+    /// * `statistic` - Computes the covariance and correlation coefficient of two series.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(Matrix, Matrix), anyhow::Error>` - The covariance and correlation matrices, or an error.
+    fn calculate_matrices<F>(
+        values: &[Vec<f64>],
+        statistic: F,
+    ) -> Result<(Matrix, Matrix), anyhow::Error>
+    where
+        F: Fn(&[f64], &[f64]) -> Result<(f64, f64), anyhow::Error>,
+    {
+        let size = values.len();
+        let mut covariance = vec![vec![0.0; size]; size];
+        let mut correlation = vec![vec![0.0; size]; size];
+
+        for i in 0..size {
+            for j in i..size {
+                let (cov, corr) = statistic(&values[i], &values[j])?;
+
+                covariance[i][j] = cov;
+                covariance[j][i] = cov;
+                correlation[i][j] = corr;
+                correlation[j][i] = corr;
+            }
+        }
+
+        Ok((covariance, correlation))
+    }
+
+    /// Calculates the simple returns of a given set of prices.
+    ///
+    /// # Arguments
+    ///
+    /// * `prices` - A slice of f64 representing the prices.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<f64>, anyhow::Error>` - Result containing a vector of simple returns or an error.
+    fn calculate_simple_returns(prices: &[f64]) -> Result<Vec<f64>, anyhow::Error> {
+        if prices.len() < 2 {
+            return Err(InsufficientData(
+                "Not enough price points to calculate simple returns.".to_string(),
+            )
+            .into());
+        }
+
+        let simple_returns = prices
+            .windows(2)
+            .map(|window| {
+                let (p1, p2) = (window[0], window[1]);
+                p2 / p1 - 1.0
+            })
+            .collect();
+
+        Ok(simple_returns)
     }
 
     /// Calculates the log returns of a given set of prices.
@@ -268,9 +1665,12 @@ impl HistoricalData {
     /// # Returns
     ///
     /// * `Result<Vec<f64>, anyhow::Error>` - Result containing a vector of log returns or an error.
-    fn calculate_log_returns(prices: &Vec<f64>) -> Result<Vec<f64>, anyhow::Error> {
+    fn calculate_log_returns(prices: &[f64]) -> Result<Vec<f64>, anyhow::Error> {
         if prices.len() < 2 {
-            return Err(anyhow!("Not enough price points to calculate log returns."));
+            return Err(InsufficientData(
+                "Not enough price points to calculate log returns.".to_string(),
+            )
+            .into());
         }
 
         let log_returns = prices
@@ -284,86 +1684,37 @@ impl HistoricalData {
         Ok(log_returns)
     }
 
-    /// Calculates the standard deviation of a given set of log returns.
+    /// Calculates the annualized standard deviation of a given set of log returns.
     ///
     /// # Arguments
     ///
     /// * `log_returns` - A vector of f64 representing the log returns.
+    /// * `annualization_factor` - The number of bars in a year.
+    /// * `estimation` - The denominator of the variance.
     ///
     /// # Returns
     ///
     /// * `Result<f64, anyhow::Error>` - Result containing the standard deviation or an error.
-    fn calculate_standard_deviation(log_returns: &Vec<f64>) -> Result<f64, anyhow::Error> {
-        if log_returns.is_empty() {
-            return Err(anyhow!(
-                "No log returns available to calculate standard deviation."
-            ));
+    fn calculate_standard_deviation(
+        log_returns: &[f64],
+        annualization_factor: f64,
+        estimation: Estimation,
+    ) -> Result<f64, anyhow::Error> {
+        if log_returns.len() < estimation.min_len() {
+            return Err(InsufficientData(format!(
+                "Not enough log returns <{}> to calculate standard deviation.",
+                log_returns.len()
+            ))
+            .into());
         }
 
         let mean = log_returns.iter().sum::<f64>() / log_returns.len() as f64;
-        let variance =
-            log_returns.iter().map(|&x| (x - mean).powi(2)).sum::<f64>() / log_returns.len() as f64;
+        let variance = log_returns.iter().map(|&x| (x - mean).powi(2)).sum::<f64>()
+            / estimation.denominator(log_returns.len());
 
-        let daily_volatility = variance.sqrt();
-        let annualized_volatility = daily_volatility * (252f64).sqrt();
+        let bar_volatility = variance.sqrt();
+        let annualized_volatility = bar_volatility * annualization_factor.sqrt();
 
         Ok(annualized_volatility)
     }
-
-    /// Builds the URL for fetching historical data for a given token from Yahoo Finance API.
-    ///
-    /// # Arguments
-    ///
-    /// * `token` - The token for which to build the URL.
-    /// * `start_date` - The start date for fetching historical data.
-    ///
-    /// # Returns
-    ///
-    /// * `String` - The formatted URL.
-    fn build_url(token: &Token, start_date: &DateTime<Local>) -> String {
-        format!(
-            "
-            https://query1.finance.yahoo.com/v8/finance/chart/{}?\
-            period1={}&period2={}&interval=1d\
-            &includePrePost=true&events=div%7Csplit%7Cearn&&lang=en-US&region=US",
-            token.id(),
-            start_date.timestamp(),
-            Local::now().timestamp()
-        )
-    }
-
-    /// Builds the required headers for the request to Yahoo Finance API.
-    ///
-    /// # Returns
-    ///
-    /// * `HeaderMap` - The header map.
-    fn build_headers() -> HeaderMap {
-        let mut headers = header::HeaderMap::new();
-        headers.insert("accept", "*/*".parse().unwrap());
-        headers.insert("accept-language", "en-US,en;q=0.9".parse().unwrap());
-        headers.insert(header::COOKIE, "tbla_id=a5febe28-3e14-4e8a-9825-c65fd3fc6c36-tuctcfc44af; axids=gam=y-v1eCrANE2uJXiPE.3E3uKQDFMVx4Dm0z~A&dv360=eS1lVE94cmsxRTJ1R2x3X3ZGVncxaXFyQjh4MTIxM3FRT35B&ydsp=y-koEQZ3pE2uLoHEGsLWwbqWpXQ.LtQptR~A&tbla=y-RVUe5pxE2uKqZVU.LIHwCi.K6Zc9hDFW~A; GUC=AQEBCAFm1UZnBEIh9QTL&s=AQAAAIRyAHJI&g=ZtP2pw; A1=d=AQABBC20_mUCEBJrnEMPcERJE6Sojvi1WLgFEgEBCAFG1WYEZ6-0b2UB_eMBAAcILbT-Zfi1WLg&S=AQAAAnLpkVOCnX8OdJc3xb1gdhQ; A3=d=AQABBC20_mUCEBJrnEMPcERJE6Sojvi1WLgFEgEBCAFG1WYEZ6-0b2UB_eMBAAcILbT-Zfi1WLg&S=AQAAAnLpkVOCnX8OdJc3xb1gdhQ; A1S=d=AQABBC20_mUCEBJrnEMPcERJE6Sojvi1WLgFEgEBCAFG1WYEZ6-0b2UB_eMBAAcILbT-Zfi1WLg&S=AQAAAnLpkVOCnX8OdJc3xb1gdhQ; cmp=t=1725167265&j=0&u=1---; gpp=DBAA; gpp_sid=-1; _cb=CfOyz-z9nkyD5KVwX; PRF=t%3DBTC-USD%252B%255EGSPC; _cb_svref=https%3A%2F%2Flevenstein.net%2F; _chartbeat2=.1725167265593.1725171315082.1.CaCCrsVFaIABCsBtRB40Rn6D8gESP.2".parse().unwrap());
-        headers.insert("dnt", "1".parse().unwrap());
-        headers.insert("origin", "https://finance.yahoo.com".parse().unwrap());
-        headers.insert("priority", "u=1, i".parse().unwrap());
-        headers.insert(
-            "referer",
-            "https://finance.yahoo.com/quote/BTC-USD/chart/"
-                .parse()
-                .unwrap(),
-        );
-        headers.insert(
-            "sec-ch-ua",
-            "\"Not;A=Brand\";v=\"24\", \"Chromium\";v=\"128\""
-                .parse()
-                .unwrap(),
-        );
-        headers.insert("sec-ch-ua-mobile", "?0".parse().unwrap());
-        headers.insert("sec-ch-ua-platform", "\"macOS\"".parse().unwrap());
-        headers.insert("sec-fetch-dest", "empty".parse().unwrap());
-        headers.insert("sec-fetch-mode", "cors".parse().unwrap());
-        headers.insert("sec-fetch-site", "same-site".parse().unwrap());
-        headers.insert("user-agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/128.0.0.0 Safari/537.36".parse().unwrap());
-
-        headers
-    }
 }
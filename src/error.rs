@@ -0,0 +1,322 @@
+use crate::request::RequestError;
+use crate::source::yahoo::chart::YahooError;
+use crate::source::SourceKind;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// Not enough data to compute a statistic, e.g. no bars common to two tokens.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct InsufficientData(pub String);
+
+/// The price source does not know the requested symbol.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct UnknownSymbol(pub String);
+
+/// The price source does not serve the request, e.g. hourly bars or equities.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct Unsupported(pub String);
+
+/// A fetch of the bars of a token from a price source failed.
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to fetch token<{symbol}> from {}.", provider.id())]
+pub struct FetchError {
+    pub symbol: String,
+    pub provider: SourceKind,
+    #[source]
+    pub error: anyhow::Error,
+}
+
+/// Errors returned by the API, rendered as `{code, message, details}` JSON bodies.
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    /// A query parameter is missing or invalid (400).
+    #[error("{message}")]
+    Validation {
+        message: String,
+        parameter: Option<String>,
+    },
+    /// The price source does not serve the request (400).
+    #[error("{message}")]
+    Unsupported {
+        message: String,
+        provider: Option<SourceKind>,
+    },
+    /// The requested resource does not exist (404).
+    #[error("{0}")]
+    NotFound(String),
+    /// The price source does not know the symbol (404).
+    #[error("{message}")]
+    UnknownSymbol {
+        message: String,
+        symbol: Option<String>,
+        provider: Option<SourceKind>,
+    },
+    /// The fetched data is not enough to compute the statistic (422).
+    #[error("{0}")]
+    InsufficientData(String),
+    /// The price source failed or answered with an unexpected payload (502).
+    #[error("{message}")]
+    Upstream {
+        message: String,
+        provider: Option<SourceKind>,
+        status: Option<u16>,
+    },
+    /// The price source is rate limiting, down or unreachable (503).
+    #[error("{message}")]
+    Unavailable {
+        message: String,
+        provider: Option<SourceKind>,
+        status: Option<u16>,
+    },
+    /// The price source did not answer in time (504).
+    #[error("{message}")]
+    Timeout {
+        message: String,
+        provider: Option<SourceKind>,
+        timeout_secs: u64,
+    },
+    /// Any other failure (500).
+    #[error("{0}")]
+    Internal(String),
+}
+
+/// JSON body of an error response.
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    code: &'static str,
+    message: String,
+    details: Value,
+}
+
+impl ApiError {
+    /// Creates a validation error of a query parameter.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameter` - The name of the query parameter.
+    /// * `message` - The error message.
+    ///
+    /// # Returns
+    ///
+    /// * `ApiError` - The validation error.
+    pub fn invalid<S: Into<String>>(parameter: &str, message: S) -> ApiError {
+        ApiError::Validation {
+            message: message.into(),
+            parameter: Some(parameter.to_string()),
+        }
+    }
+
+    /// Creates a validation error not tied to a single query parameter.
+    pub fn validation<S: Into<String>>(message: S) -> ApiError {
+        ApiError::Validation {
+            message: message.into(),
+            parameter: None,
+        }
+    }
+
+    /// Returns the machine-readable code of the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::Validation { .. } => "invalid_request",
+            ApiError::Unsupported { .. } => "unsupported_request",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::UnknownSymbol { .. } => "unknown_symbol",
+            ApiError::InsufficientData(_) => "insufficient_data",
+            ApiError::Upstream { .. } => "upstream_error",
+            ApiError::Unavailable { .. } => "upstream_unavailable",
+            ApiError::Timeout { .. } => "upstream_timeout",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    /// Returns the details of the error, an empty object when there are none.
+    pub fn details(&self) -> Value {
+        let mut details = Map::new();
+        let mut insert = |key: &str, value: Value| {
+            if !value.is_null() {
+                details.insert(key.to_string(), value);
+            }
+        };
+
+        match self {
+            ApiError::Validation { parameter, .. } => insert("parameter", json!(parameter)),
+            ApiError::Unsupported { provider, .. } => insert("source", json!(provider)),
+            ApiError::UnknownSymbol {
+                symbol, provider, ..
+            } => {
+                insert("symbol", json!(symbol));
+                insert("source", json!(provider));
+            }
+            ApiError::Upstream {
+                provider, status, ..
+            }
+            | ApiError::Unavailable {
+                provider, status, ..
+            } => {
+                insert("source", json!(provider));
+                insert("upstream_status", json!(status));
+            }
+            ApiError::Timeout {
+                provider,
+                timeout_secs,
+                ..
+            } => {
+                insert("source", json!(provider));
+                insert("timeout_secs", json!(timeout_secs));
+            }
+            ApiError::NotFound(_) | ApiError::InsufficientData(_) | ApiError::Internal(_) => {}
+        }
+
+        Value::Object(details)
+    }
+
+    /// Classifies an upstream request error.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - The request error, after retries.
+    /// * `symbol` - The symbol being fetched, if known.
+    /// * `provider` - The price source being fetched from, if known.
+    ///
+    /// # Returns
+    ///
+    /// * `ApiError` - The API error matching the request error.
+    fn from_request_error(
+        err: &RequestError,
+        symbol: Option<String>,
+        provider: Option<SourceKind>,
+    ) -> ApiError {
+        let name = provider
+            .as_ref()
+            .map_or("The price source", |provider| provider.id());
+
+        match err {
+            RequestError::Timeout { timeout, .. } => ApiError::Timeout {
+                message: format!(
+                    "{} did not answer within {} seconds.",
+                    name,
+                    timeout.as_secs()
+                ),
+                provider,
+                timeout_secs: timeout.as_secs(),
+            },
+            RequestError::Status { status, .. } if status.as_u16() == 404 => {
+                ApiError::UnknownSymbol {
+                    message: match &symbol {
+                        Some(symbol) => format!("{} does not know token<{}>.", name, symbol),
+                        None => format!("{} does not know the requested symbol.", name),
+                    },
+                    symbol,
+                    provider,
+                }
+            }
+            RequestError::Status { status, .. } if matches!(status.as_u16(), 429 | 503) => {
+                ApiError::Unavailable {
+                    message: format!("{} is unavailable, status {}.", name, status),
+                    provider,
+                    status: Some(status.as_u16()),
+                }
+            }
+            RequestError::Status { status, .. } => ApiError::Upstream {
+                message: format!("{} failed, status {}.", name, status),
+                provider,
+                status: Some(status.as_u16()),
+            },
+            RequestError::Transport { source: err, .. } => ApiError::Unavailable {
+                message: format!("{} is unreachable: {}", name, err),
+                provider,
+                status: None,
+            },
+            RequestError::Decode { message, .. } => ApiError::Upstream {
+                message: format!("{} returned an unexpected payload: {}", name, message),
+                provider,
+                status: None,
+            },
+            RequestError::Url { .. } => ApiError::Internal(err.to_string()),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    /// Classifies an error by the typed errors found in its chain.
+    fn from(err: anyhow::Error) -> ApiError {
+        let fetch = err.chain().find_map(|e| e.downcast_ref::<FetchError>());
+        let symbol = fetch.map(|fetch| fetch.symbol.clone());
+        let provider = fetch.map(|fetch| fetch.provider);
+
+        let api_error = err
+            .chain()
+            .find_map(|e| {
+                if let Some(err) = e.downcast_ref::<InsufficientData>() {
+                    return Some(ApiError::InsufficientData(err.to_string()));
+                }
+                if let Some(err) = e.downcast_ref::<Unsupported>() {
+                    return Some(ApiError::Unsupported {
+                        message: err.to_string(),
+                        provider,
+                    });
+                }
+                if let Some(err) = e.downcast_ref::<UnknownSymbol>() {
+                    return Some(ApiError::UnknownSymbol {
+                        message: err.to_string(),
+                        symbol: symbol.clone(),
+                        provider,
+                    });
+                }
+                if let Some(err) = e.downcast_ref::<YahooError>() {
+                    return Some(match err {
+                        YahooError::Api { code, .. } if code == "Not Found" => {
+                            ApiError::UnknownSymbol {
+                                message: err.to_string(),
+                                symbol: symbol.clone(),
+                                provider,
+                            }
+                        }
+                        _ => ApiError::Upstream {
+                            message: err.to_string(),
+                            provider,
+                            status: None,
+                        },
+                    });
+                }
+                e.downcast_ref::<RequestError>()
+                    .map(|err| ApiError::from_request_error(err, symbol.clone(), provider))
+            })
+            .unwrap_or_else(|| ApiError::Internal(err.to_string()));
+
+        match api_error.status_code().as_u16() {
+            500 => error!("{:?}", err),
+            502..=504 => warn!("{}: {}", api_error, err.root_cause()),
+            _ => {}
+        }
+
+        api_error
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::Validation { .. } | ApiError::Unsupported { .. } => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) | ApiError::UnknownSymbol { .. } => StatusCode::NOT_FOUND,
+            ApiError::InsufficientData(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::Upstream { .. } => StatusCode::BAD_GATEWAY,
+            ApiError::Unavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(ErrorBody {
+            code: self.code(),
+            message: self.to_string(),
+            details: self.details(),
+        })
+    }
+}
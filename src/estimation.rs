@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+/// Denominator of the variance and covariance estimates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Estimation {
+    /// Divides by `N`, the maximum-likelihood estimate.
+    #[default]
+    Population,
+    /// Divides by `N - 1`, the unbiased estimate.
+    Sample,
+}
+
+impl Estimation {
+    /// Creates an `Estimation` enum from a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `estimation` - A string slice representing the estimation name.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Estimation>` - Representing the estimation if valid, `None` if invalid.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(estimation: &str) -> Option<Estimation> {
+        match estimation.to_lowercase().as_str() {
+            "population" | "pop" => Some(Estimation::Population),
+            "sample" | "unbiased" => Some(Estimation::Sample),
+            _ => None,
+        }
+    }
+
+    /// Returns the denominator of an estimate over a number of values.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The number of values.
+    ///
+    /// # Returns
+    ///
+    /// * `f64` - `len` for population estimates, `len - 1` for sample ones.
+    pub fn denominator(&self, len: usize) -> f64 {
+        match self {
+            Estimation::Population => len as f64,
+            Estimation::Sample => len as f64 - 1.0,
+        }
+    }
+
+    /// Returns the fewest values an estimate needs.
+    pub fn min_len(&self) -> usize {
+        match self {
+            Estimation::Population => 1,
+            Estimation::Sample => 2,
+        }
+    }
+}
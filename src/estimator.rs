@@ -0,0 +1,268 @@
+use crate::error::InsufficientData;
+use crate::series::Bar;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::LN_2;
+
+/// Range-based volatility estimators, computed from the OHLC fields of bars.
+///
+/// They use the intrabar range on top of the close, so they converge faster
+/// than the close-to-close estimator. Bars built from single-price
+/// observations have no range and underestimate volatility.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Estimator {
+    /// High-low range, assumes no drift and no opening jumps.
+    Parkinson,
+    /// High-low and open-close ranges, assumes no drift and no opening jumps.
+    GarmanKlass,
+    /// High-low-open-close ranges, unbiased under drift.
+    RogersSatchell,
+    /// Overnight, open-close and Rogers–Satchell variances, robust to drift and opening jumps.
+    YangZhang,
+}
+
+impl Estimator {
+    /// All estimators, in the order they are reported.
+    pub const ALL: [Estimator; 4] = [
+        Estimator::Parkinson,
+        Estimator::GarmanKlass,
+        Estimator::RogersSatchell,
+        Estimator::YangZhang,
+    ];
+
+    /// Creates an `Estimator` enum from a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `estimator` - A string slice representing the estimator name.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Estimator>` - Representing the estimator if valid, `None` if invalid.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(estimator: &str) -> Option<Estimator> {
+        match estimator.trim().to_lowercase().as_str() {
+            "parkinson" => Some(Estimator::Parkinson),
+            "garman_klass" | "gk" => Some(Estimator::GarmanKlass),
+            "rogers_satchell" | "rs" => Some(Estimator::RogersSatchell),
+            "yang_zhang" | "yz" => Some(Estimator::YangZhang),
+            _ => None,
+        }
+    }
+
+    /// Returns the identifier of the estimator.
+    pub fn id(&self) -> &str {
+        match *self {
+            Estimator::Parkinson => "parkinson",
+            Estimator::GarmanKlass => "garman_klass",
+            Estimator::RogersSatchell => "rogers_satchell",
+            Estimator::YangZhang => "yang_zhang",
+        }
+    }
+
+    /// Creates a list of estimators from a comma-separated string, `all` selecting every one.
+    ///
+    /// # Arguments
+    ///
+    /// * `estimators` - A string slice of comma-separated estimator names.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<Estimator>>` - The distinct estimators in `ALL` order, `None` if a name is invalid.
+    pub fn from_list(estimators: &str) -> Option<Vec<Estimator>> {
+        let mut selected = Vec::new();
+
+        for name in estimators.split(',').filter(|s| !s.trim().is_empty()) {
+            if name.trim().eq_ignore_ascii_case("all") {
+                return Some(Estimator::ALL.to_vec());
+            }
+            selected.push(Estimator::from_str(name)?);
+        }
+
+        Some(
+            Estimator::ALL
+                .into_iter()
+                .filter(|estimator| selected.contains(estimator))
+                .collect(),
+        )
+    }
+
+    /// Calculates the per-bar variance of a series of bars.
+    ///
+    /// Bars with a non-positive price are skipped, and so are close-only bars,
+    /// which sources fill with the close and which carry no intraday range.
+    /// Yang–Zhang pairs each bar with the bar before it, skipping the pairs
+    /// where either was skipped, so overnight returns never span several bars.
+    ///
+    /// # Arguments
+    ///
+    /// * `bars` - The bars in chronological order.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(f64, usize), anyhow::Error>` - Result containing the per-bar variance and the number of observations used, or an error.
+    pub fn bar_variance(&self, bars: &[Bar]) -> Result<(f64, usize), anyhow::Error> {
+        let is_usable = |bar: &Bar| {
+            bar.open > 0.0
+                && bar.high > 0.0
+                && bar.low > 0.0
+                && bar.close > 0.0
+                && !bar.is_close_only()
+        };
+
+        let usable: Vec<&Bar> = bars.iter().filter(|bar| is_usable(bar)).collect();
+        // Overnight returns need the previous close, so Yang–Zhang works on consecutive pairs
+        let periods: Vec<(&Bar, &Bar)> = bars
+            .windows(2)
+            .filter(|w| is_usable(&w[0]) && is_usable(&w[1]))
+            .map(|w| (&w[0], &w[1]))
+            .collect();
+
+        let (observations, required) = match self {
+            Estimator::YangZhang => (periods.len(), 2),
+            _ => (usable.len(), 1),
+        };
+
+        if observations < required {
+            return Err(InsufficientData(format!(
+                "Not enough OHLC observations <{}> for the {} estimator, at least {} are required.",
+                observations,
+                self.id(),
+                required
+            ))
+            .into());
+        }
+
+        let n = observations as f64;
+        let variance = match self {
+            Estimator::Parkinson => {
+                usable
+                    .iter()
+                    .map(|bar| (bar.high / bar.low).ln().powi(2))
+                    .sum::<f64>()
+                    / (4.0 * LN_2 * n)
+            }
+            Estimator::GarmanKlass => {
+                usable
+                    .iter()
+                    .map(|bar| {
+                        0.5 * (bar.high / bar.low).ln().powi(2)
+                            - (2.0 * LN_2 - 1.0) * (bar.close / bar.open).ln().powi(2)
+                    })
+                    .sum::<f64>()
+                    / n
+            }
+            Estimator::RogersSatchell => {
+                usable
+                    .iter()
+                    .map(|bar| Self::rogers_satchell(bar))
+                    .sum::<f64>()
+                    / n
+            }
+            Estimator::YangZhang => {
+                let overnight: Vec<f64> = periods
+                    .iter()
+                    .map(|(prev, bar)| (bar.open / prev.close).ln())
+                    .collect();
+                let open_close: Vec<f64> = periods
+                    .iter()
+                    .map(|(_, bar)| (bar.close / bar.open).ln())
+                    .collect();
+                let rogers_satchell = periods
+                    .iter()
+                    .map(|(_, bar)| Self::rogers_satchell(bar))
+                    .sum::<f64>()
+                    / n;
+
+                let k = 0.34 / (1.34 + (n + 1.0) / (n - 1.0));
+
+                Self::sample_variance(&overnight)
+                    + k * Self::sample_variance(&open_close)
+                    + (1.0 - k) * rogers_satchell
+            }
+        };
+
+        Ok((variance.max(0.0), observations))
+    }
+
+    /// Returns the Rogers–Satchell variance term of a bar.
+    fn rogers_satchell(bar: &Bar) -> f64 {
+        (bar.high / bar.close).ln() * (bar.high / bar.open).ln()
+            + (bar.low / bar.close).ln() * (bar.low / bar.open).ln()
+    }
+
+    /// Returns the sample variance (`n - 1` denominator) of at least two values.
+    fn sample_variance(values: &[f64]) -> f64 {
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+
+        values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Estimator;
+    use crate::series::Bar;
+    use chrono::NaiveDate;
+
+    fn bar(day: u32, open: f64, high: f64, low: f64, close: f64) -> Bar {
+        Bar {
+            date: NaiveDate::from_ymd_opt(2024, 1, day)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+            open,
+            high,
+            low,
+            close,
+            volume: None,
+            adjclose: None,
+        }
+    }
+
+    /// Bars around a close-only bar, the bars after it scaled by a factor.
+    fn bars_around_close_only(scale: f64) -> Vec<Bar> {
+        vec![
+            bar(1, 100.0, 103.0, 99.0, 101.0),
+            bar(2, 101.5, 104.0, 100.0, 102.0),
+            bar(3, 101.0, 102.5, 98.0, 99.0),
+            bar(4, 250.0, 250.0, 250.0, 250.0),
+            bar(5, 98.0 * scale, 101.0 * scale, 97.0 * scale, 100.0 * scale),
+            bar(8, 100.5 * scale, 102.0 * scale, 99.5 * scale, 101.0 * scale),
+        ]
+    }
+
+    #[test]
+    fn yang_zhang_skips_pairs_around_close_only_bars() {
+        let (variance, observations) = Estimator::YangZhang
+            .bar_variance(&bars_around_close_only(1.0))
+            .unwrap();
+
+        // (1, 2), (2, 3) and (5, 8), no overnight return spans the close-only bar
+        assert_eq!(observations, 3);
+
+        // Rescaling the bars after the close-only bar leaves every used return unchanged
+        let (rescaled, _) = Estimator::YangZhang
+            .bar_variance(&bars_around_close_only(10.0))
+            .unwrap();
+        assert!((variance - rescaled).abs() < 1e-15);
+    }
+
+    #[test]
+    fn range_estimators_skip_close_only_bars() {
+        let bars = bars_around_close_only(1.0);
+
+        for estimator in [
+            Estimator::Parkinson,
+            Estimator::GarmanKlass,
+            Estimator::RogersSatchell,
+        ] {
+            let (_, observations) = estimator.bar_variance(&bars).unwrap();
+            assert_eq!(observations, 5);
+        }
+
+        let close_only = [bar(1, 1.0, 1.0, 1.0, 1.0), bar(2, 2.0, 2.0, 2.0, 2.0)];
+        assert!(Estimator::Parkinson.bar_variance(&close_only).is_err());
+    }
+}
@@ -0,0 +1,414 @@
+use crate::error::InsufficientData;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// RiskMetrics decay factor of daily returns.
+pub const DEFAULT_LAMBDA: f64 = 0.94;
+
+/// Fewest returns a GARCH(1,1) model is fitted on.
+const MIN_GARCH_OBSERVATIONS: usize = 30;
+
+/// Iterations of the likelihood maximization before giving up on convergence.
+const MAX_ITERATIONS: usize = 2000;
+
+/// Spread of the simplex values below which the maximization has converged.
+const TOLERANCE: f64 = 1e-10;
+
+/// Persistence above which a GARCH fit is reported as integrated, without a long-run volatility.
+const MAX_STATIONARY_PERSISTENCE: f64 = 0.9999;
+
+/// Conditional volatility models.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForecastModel {
+    /// RiskMetrics exponentially weighted moving average with a decay factor.
+    Ewma { lambda: f64 },
+    /// GARCH(1,1) fitted by Gaussian maximum likelihood.
+    Garch,
+}
+
+/// Parameters of a fitted model.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum FittedModel {
+    Ewma {
+        lambda: f64,
+    },
+    Garch {
+        omega: f64,
+        alpha: f64,
+        beta: f64,
+        /// `alpha + beta`, how slowly shocks decay.
+        persistence: f64,
+        /// Per-bar volatility the forecasts revert to, `None` when shocks never decay.
+        long_run_volatility: Option<f64>,
+        log_likelihood: f64,
+        converged: bool,
+    },
+}
+
+/// A fitted model with its conditional variances.
+#[derive(Debug, Clone)]
+pub struct ModelFit {
+    pub model: FittedModel,
+    /// Per-bar variance of each return given the returns before it.
+    pub variances: Vec<f64>,
+    /// Per-bar variance of the bar following the last return.
+    pub next_variance: f64,
+}
+
+impl ForecastModel {
+    /// Creates a `ForecastModel` from a string, EWMA using the RiskMetrics decay factor.
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - A string slice representing the model name.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<ForecastModel>` - Representing the model if valid, `None` if invalid.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(model: &str) -> Option<ForecastModel> {
+        match model.to_lowercase().as_str() {
+            "ewma" | "riskmetrics" => Some(ForecastModel::Ewma {
+                lambda: DEFAULT_LAMBDA,
+            }),
+            "garch" | "garch11" | "garch(1,1)" => Some(ForecastModel::Garch),
+            _ => None,
+        }
+    }
+
+    /// Fits the model on a series of returns.
+    ///
+    /// Both models are seeded with the sample variance of the returns. EWMA
+    /// assumes zero-mean returns, as RiskMetrics does, while GARCH is fitted on
+    /// the demeaned returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `returns` - The log returns in chronological order.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ModelFit, anyhow::Error>` - Result containing the fitted model, or an error.
+    pub fn fit(&self, returns: &[f64]) -> Result<ModelFit, anyhow::Error> {
+        let required = match self {
+            ForecastModel::Ewma { .. } => 2,
+            ForecastModel::Garch => MIN_GARCH_OBSERVATIONS,
+        };
+
+        if returns.len() < required {
+            return Err(InsufficientData(format!(
+                "Not enough returns <{}> to fit the model, at least {} are required.",
+                returns.len(),
+                required
+            ))
+            .into());
+        }
+
+        match *self {
+            ForecastModel::Ewma { lambda } => Ok(Self::fit_ewma(returns, lambda)),
+            ForecastModel::Garch => Ok(Self::fit_garch(returns)),
+        }
+    }
+
+    /// Runs the EWMA recursion `s2[t+1] = lambda * s2[t] + (1 - lambda) * r[t]^2`.
+    fn fit_ewma(returns: &[f64], lambda: f64) -> ModelFit {
+        let mut variance = returns.iter().map(|r| r.powi(2)).sum::<f64>() / returns.len() as f64;
+        let mut variances = Vec::with_capacity(returns.len());
+
+        for r in returns {
+            variances.push(variance);
+            variance = lambda * variance + (1.0 - lambda) * r.powi(2);
+        }
+
+        ModelFit {
+            model: FittedModel::Ewma { lambda },
+            variances,
+            next_variance: variance,
+        }
+    }
+
+    /// Fits GARCH(1,1) by maximizing the Gaussian log-likelihood with Nelder–Mead.
+    ///
+    /// The parameters are searched in an unconstrained space mapping onto
+    /// `omega > 0`, `alpha, beta >= 0` and `alpha + beta < 1`, so every fit is
+    /// stationary.
+    fn fit_garch(returns: &[f64]) -> ModelFit {
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let residuals: Vec<f64> = returns.iter().map(|r| r - mean).collect();
+        let sample_variance =
+            residuals.iter().map(|e| e.powi(2)).sum::<f64>() / residuals.len() as f64;
+
+        // `omega` is scaled by the sample variance, keeping the search well conditioned
+        let params = |x: &[f64; 3]| {
+            let (a, b) = (x[1].exp(), x[2].exp());
+            let omega = sample_variance * x[0].exp();
+            (omega, a / (1.0 + a + b), b / (1.0 + a + b))
+        };
+
+        let negative_log_likelihood = |x: &[f64; 3]| {
+            let (omega, alpha, beta) = params(x);
+            let (variances, _) =
+                Self::garch_variances(&residuals, omega, alpha, beta, sample_variance);
+
+            residuals
+                .iter()
+                .zip(&variances)
+                .map(|(e, s2)| 0.5 * ((2.0 * PI * s2).ln() + e.powi(2) / s2))
+                .sum::<f64>()
+        };
+
+        // Start from a typical daily fit, alpha = 0.05 and beta = 0.90 around the sample variance
+        let start = [0.05f64.ln(), 0.0, 18.0f64.ln()];
+        let (x, converged) = Self::nelder_mead(negative_log_likelihood, start);
+
+        let (omega, alpha, beta) = params(&x);
+        let (variances, next_variance) =
+            Self::garch_variances(&residuals, omega, alpha, beta, sample_variance);
+        let persistence = alpha + beta;
+
+        ModelFit {
+            model: FittedModel::Garch {
+                omega,
+                alpha,
+                beta,
+                persistence,
+                long_run_volatility: (persistence < MAX_STATIONARY_PERSISTENCE)
+                    .then(|| (omega / (1.0 - persistence)).sqrt()),
+                log_likelihood: -negative_log_likelihood(&x),
+                converged,
+            },
+            variances,
+            next_variance,
+        }
+    }
+
+    /// Runs the GARCH(1,1) recursion `s2[t+1] = omega + alpha * e[t]^2 + beta * s2[t]`.
+    ///
+    /// # Returns
+    ///
+    /// * `(Vec<f64>, f64)` - The variance of each residual and of the next bar.
+    fn garch_variances(
+        residuals: &[f64],
+        omega: f64,
+        alpha: f64,
+        beta: f64,
+        seed: f64,
+    ) -> (Vec<f64>, f64) {
+        let mut variance = seed;
+        let mut variances = Vec::with_capacity(residuals.len());
+
+        for e in residuals {
+            variances.push(variance);
+            variance = omega + alpha * e.powi(2) + beta * variance;
+        }
+
+        (variances, variance)
+    }
+
+    /// Minimizes a function of three parameters with the Nelder–Mead simplex method.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The function to minimize.
+    /// * `start` - The starting point.
+    ///
+    /// # Returns
+    ///
+    /// * `([f64; 3], bool)` - The best point found and whether the search converged.
+    fn nelder_mead<F: Fn(&[f64; 3]) -> f64>(f: F, start: [f64; 3]) -> ([f64; 3], bool) {
+        let mut simplex: Vec<([f64; 3], f64)> = (0..=3)
+            .map(|i| {
+                let mut x = start;
+                if i > 0 {
+                    x[i - 1] += 0.5;
+                }
+                (x, f(&x))
+            })
+            .collect();
+
+        let combine = |a: &[f64; 3], b: &[f64; 3], t: f64| -> [f64; 3] {
+            [
+                a[0] + t * (b[0] - a[0]),
+                a[1] + t * (b[1] - a[1]),
+                a[2] + t * (b[2] - a[2]),
+            ]
+        };
+
+        for _ in 0..MAX_ITERATIONS {
+            simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+            if (simplex[3].1 - simplex[0].1).abs() < TOLERANCE {
+                return (simplex[0].0, true);
+            }
+
+            let centroid = {
+                let mut c = [0.0; 3];
+                for (x, _) in &simplex[..3] {
+                    for j in 0..3 {
+                        c[j] += x[j] / 3.0;
+                    }
+                }
+                c
+            };
+
+            let worst = simplex[3];
+            let reflected = combine(&centroid, &worst.0, -1.0);
+            let reflected_value = f(&reflected);
+
+            if reflected_value < simplex[0].1 {
+                let expanded = combine(&centroid, &worst.0, -2.0);
+                let expanded_value = f(&expanded);
+                simplex[3] = if expanded_value < reflected_value {
+                    (expanded, expanded_value)
+                } else {
+                    (reflected, reflected_value)
+                };
+            } else if reflected_value < simplex[2].1 {
+                simplex[3] = (reflected, reflected_value);
+            } else {
+                let contracted = combine(&centroid, &worst.0, 0.5);
+                let contracted_value = f(&contracted);
+
+                if contracted_value < worst.1 {
+                    simplex[3] = (contracted, contracted_value);
+                } else {
+                    // Shrink every point towards the best one
+                    let best = simplex[0].0;
+                    for point in simplex.iter_mut().skip(1) {
+                        let x = combine(&best, &point.0, 0.5);
+                        *point = (x, f(&x));
+                    }
+                }
+            }
+        }
+
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        (simplex[0].0, false)
+    }
+}
+
+impl FittedModel {
+    /// Forecasts the per-bar variance of the bars following the fitted returns.
+    ///
+    /// EWMA forecasts are flat, GARCH forecasts revert to the long-run
+    /// variance at the rate of the persistence.
+    ///
+    /// # Arguments
+    ///
+    /// * `next_variance` - The variance of the bar following the last return.
+    /// * `horizon` - The number of bars to forecast.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<f64>` - The per-bar variance of each bar ahead.
+    pub fn forecast(&self, next_variance: f64, horizon: usize) -> Vec<f64> {
+        match *self {
+            FittedModel::Ewma { .. } => vec![next_variance; horizon],
+            FittedModel::Garch {
+                omega, persistence, ..
+            } => {
+                // `s2[h+1] = omega + persistence * s2[h]`, stable even close to integration
+                let mut variance = next_variance;
+                (0..horizon)
+                    .map(|h| {
+                        if h > 0 {
+                            variance = omega + persistence * variance;
+                        }
+                        variance
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FittedModel, ForecastModel};
+    use std::f64::consts::PI;
+
+    /// Simulates GARCH(1,1) returns, drawing normal shocks from a seeded xorshift generator.
+    fn simulate_garch(omega: f64, alpha: f64, beta: f64, len: usize) -> Vec<f64> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut uniform = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            ((state >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+        };
+
+        let mut variance = omega / (1.0 - alpha - beta);
+        (0..len)
+            .map(|_| {
+                let shock = (-2.0 * uniform().ln()).sqrt() * (2.0 * PI * uniform()).cos();
+                let r = variance.sqrt() * shock;
+                variance = omega + alpha * r.powi(2) + beta * variance;
+                r
+            })
+            .collect()
+    }
+
+    #[test]
+    fn garch_recovers_simulated_parameters() {
+        let returns = simulate_garch(2e-6, 0.08, 0.9, 5000);
+        let fit = ForecastModel::Garch.fit(&returns).unwrap();
+
+        match fit.model {
+            FittedModel::Garch {
+                omega,
+                alpha,
+                beta,
+                long_run_volatility,
+                converged,
+                ..
+            } => {
+                assert!(converged);
+                assert!((alpha - 0.08).abs() < 0.03, "alpha {}", alpha);
+                assert!((beta - 0.9).abs() < 0.04, "beta {}", beta);
+                assert!((omega / 2e-6 - 1.0).abs() < 0.5, "omega {}", omega);
+                assert!(long_run_volatility.is_some());
+            }
+            model => panic!("Expected a GARCH fit, got {:?}", model),
+        }
+    }
+
+    #[test]
+    fn ewma_runs_the_riskmetrics_recursion() {
+        let returns = [0.01, -0.02, 0.03];
+        let fit = ForecastModel::Ewma { lambda: 0.9 }.fit(&returns).unwrap();
+
+        let seed = (0.0001 + 0.0004 + 0.0009) / 3.0;
+        let second = 0.9 * seed + 0.1 * 0.0001;
+        let third = 0.9 * second + 0.1 * 0.0004;
+        let next = 0.9 * third + 0.1 * 0.0009;
+
+        for (value, expected) in fit.variances.iter().zip([seed, second, third]) {
+            assert!((value - expected).abs() < 1e-15);
+        }
+        assert!((fit.next_variance - next).abs() < 1e-15);
+        assert_eq!(
+            fit.model.forecast(fit.next_variance, 3),
+            vec![fit.next_variance; 3]
+        );
+    }
+
+    #[test]
+    fn garch_forecasts_revert_to_the_long_run_variance() {
+        let model = FittedModel::Garch {
+            omega: 1e-6,
+            alpha: 0.1,
+            beta: 0.8,
+            persistence: 0.9,
+            long_run_volatility: Some(1e-5f64.sqrt()),
+            log_likelihood: 0.0,
+            converged: true,
+        };
+
+        let forecast = model.forecast(5e-5, 200);
+
+        assert_eq!(forecast[0], 5e-5);
+        assert!((forecast[1] - (1e-6 + 0.9 * 5e-5)).abs() < 1e-18);
+        assert!((forecast[199] - 1e-5).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,264 @@
+use crate::calendar::TradingCalendar;
+use crate::range::DateRange;
+use anyhow::anyhow;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Maximum lookback Yahoo Finance serves for intraday bars.
+const MAX_HOURLY_LOOKBACK_DAYS: i64 = 730;
+
+/// Bar interval of the historical data.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interval {
+    Hourly,
+    #[default]
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Interval {
+    /// Returns the identifier used in Yahoo Finance API for the interval.
+    pub fn id(&self) -> &str {
+        match *self {
+            Interval::Hourly => "1h",
+            Interval::Daily => "1d",
+            Interval::Weekly => "1wk",
+            Interval::Monthly => "1mo",
+        }
+    }
+
+    /// Creates an `Interval` enum from a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - A string slice representing the interval name.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Interval>` - Representing the interval if valid, `None` if invalid.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(interval: &str) -> Option<Interval> {
+        match interval.to_lowercase().as_str() {
+            "1h" | "hour" | "hourly" => Some(Interval::Hourly),
+            "1d" | "day" | "daily" => Some(Interval::Daily),
+            "1wk" | "1w" | "week" | "weekly" => Some(Interval::Weekly),
+            "1mo" | "1m" | "month" | "monthly" => Some(Interval::Monthly),
+            _ => None,
+        }
+    }
+
+    /// Parses the optional interval request parameter and checks it against the range.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Optional interval name, defaulting to daily bars.
+    /// * `range` - The requested time range.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Interval, anyhow::Error>` - The interval, or an error if it is invalid for the range.
+    pub fn from_query(
+        interval: Option<&str>,
+        range: &DateRange,
+    ) -> Result<Interval, anyhow::Error> {
+        let interval = match interval {
+            Some(value) => Interval::from_str(value)
+                .ok_or_else(|| anyhow!("Invalid interval value: {}", value))?,
+            None => Interval::default(),
+        };
+
+        interval.validate_range(range)?;
+
+        Ok(interval)
+    }
+
+    /// Returns the number of bars in a year for the given trading calendar.
+    ///
+    /// Equities trade ~252 sessions a year with seven hourly bars per regular
+    /// session, while crypto trades 365 days a year around the clock.
+    ///
+    /// # Arguments
+    ///
+    /// * `calendar` - The trading calendar of the (aligned) series.
+    ///
+    /// # Returns
+    ///
+    /// * `f64` - The annualization factor.
+    pub fn annualization_factor(&self, calendar: TradingCalendar) -> f64 {
+        match (*self, calendar) {
+            (Interval::Hourly, TradingCalendar::Equity) => 252.0 * 7.0,
+            (Interval::Hourly, TradingCalendar::Crypto) => 365.0 * 24.0,
+            (Interval::Daily, TradingCalendar::Equity) => 252.0,
+            (Interval::Daily, TradingCalendar::Crypto) => 365.0,
+            (Interval::Weekly, _) => 52.0,
+            (Interval::Monthly, _) => 12.0,
+        }
+    }
+
+    /// Returns the start of the bar a timestamp belongs to, in UTC.
+    ///
+    /// Bars of different assets are aligned on this key, so intraday timestamps
+    /// are truncated to the hour, daily ones to the date, weekly ones to the
+    /// Monday of the week and monthly ones to the first day of the month.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - The bar timestamp.
+    ///
+    /// # Returns
+    ///
+    /// * `NaiveDateTime` - The start of the bar.
+    pub fn bar_start(&self, timestamp: DateTime<Utc>) -> NaiveDateTime {
+        self.local_bar_start(timestamp, Tz::UTC)
+    }
+
+    /// Returns the start of the bar a timestamp belongs to, dated in the timezone of its exchange.
+    ///
+    /// Daily and coarser bars take the local date of the timestamp, so a
+    /// session opening before midnight UTC is not dated by the previous day.
+    /// Hourly bars stay truncated in UTC, keeping bars of different exchanges
+    /// on the same instant. Converting an instant to local time is never
+    /// ambiguous, so DST transitions cannot shift a bar.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - The bar timestamp.
+    /// * `timezone` - The timezone of the exchange.
+    ///
+    /// # Returns
+    ///
+    /// * `NaiveDateTime` - The start of the bar.
+    pub fn local_bar_start(&self, timestamp: DateTime<Utc>, timezone: Tz) -> NaiveDateTime {
+        let date = timestamp.with_timezone(&timezone).date_naive();
+
+        match *self {
+            Interval::Hourly => timestamp
+                .date_naive()
+                .and_hms_opt(timestamp.hour(), 0, 0)
+                .expect("hour of a valid timestamp"),
+            Interval::Daily => date.and_hms_opt(0, 0, 0).expect("midnight is valid"),
+            Interval::Weekly => {
+                let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                monday.and_hms_opt(0, 0, 0).expect("midnight is valid")
+            }
+            Interval::Monthly => NaiveDate::from_ymd_opt(date.year(), date.month(), 1)
+                .expect("first day of a valid month")
+                .and_hms_opt(0, 0, 0)
+                .expect("midnight is valid"),
+        }
+    }
+
+    /// Checks that the range can be served at this interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The requested time range.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), anyhow::Error>` - An error if the range exceeds the interval's limits.
+    fn validate_range(&self, range: &DateRange) -> Result<(), anyhow::Error> {
+        if *self == Interval::Hourly
+            && Utc::now() - range.start > Duration::days(MAX_HOURLY_LOOKBACK_DAYS)
+        {
+            return Err(anyhow!(
+                "Hourly data is only available for the last {} days.",
+                MAX_HOURLY_LOOKBACK_DAYS
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Interval;
+    use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
+    use chrono_tz::Tz;
+
+    fn midnight(year: i32, month: u32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    fn new_york(timestamp: (i32, u32, u32, u32, u32), interval: Interval) -> NaiveDateTime {
+        let (year, month, day, hour, minute) = timestamp;
+        let instant = Utc
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap();
+
+        interval.local_bar_start(instant, Tz::America__New_York)
+    }
+
+    #[test]
+    fn new_york_sessions_keep_their_date_across_dst_switches() {
+        // Sessions open at 09:30, 14:30 UTC in winter and 13:30 UTC in summer
+        let sessions = [
+            ((2024, 3, 8, 14, 30), midnight(2024, 3, 8)),
+            ((2024, 3, 11, 13, 30), midnight(2024, 3, 11)),
+            ((2024, 11, 1, 13, 30), midnight(2024, 11, 1)),
+            ((2024, 11, 4, 14, 30), midnight(2024, 11, 4)),
+        ];
+
+        for (timestamp, date) in sessions {
+            assert_eq!(new_york(timestamp, Interval::Daily), date);
+        }
+    }
+
+    #[test]
+    fn dst_switch_days_are_dated_by_their_local_date() {
+        // 03:30 EDT right after clocks spring forward, 23:30 EDT the same evening
+        assert_eq!(
+            new_york((2024, 3, 10, 7, 30), Interval::Daily),
+            midnight(2024, 3, 10)
+        );
+        assert_eq!(
+            new_york((2024, 3, 11, 3, 30), Interval::Daily),
+            midnight(2024, 3, 10)
+        );
+
+        // 01:30 happens twice when clocks fall back, once in EDT and once in EST
+        assert_eq!(
+            new_york((2024, 11, 3, 5, 30), Interval::Daily),
+            midnight(2024, 11, 3)
+        );
+        assert_eq!(
+            new_york((2024, 11, 3, 6, 30), Interval::Daily),
+            midnight(2024, 11, 3)
+        );
+
+        // 23:30 EST on Sunday is already Monday in UTC, but stays in the week before
+        assert_eq!(
+            new_york((2024, 11, 4, 4, 30), Interval::Daily),
+            midnight(2024, 11, 3)
+        );
+        assert_eq!(
+            new_york((2024, 11, 4, 4, 30), Interval::Weekly),
+            midnight(2024, 10, 28)
+        );
+        assert_eq!(
+            new_york((2024, 11, 4, 4, 30), Interval::Monthly),
+            midnight(2024, 11, 1)
+        );
+    }
+
+    #[test]
+    fn hourly_bars_stay_in_utc() {
+        let start = NaiveDate::from_ymd_opt(2024, 11, 3)
+            .unwrap()
+            .and_hms_opt(5, 0, 0)
+            .unwrap();
+
+        assert_eq!(new_york((2024, 11, 3, 5, 30), Interval::Hourly), start);
+        assert_eq!(
+            Interval::Hourly.bar_start(Utc.with_ymd_and_hms(2024, 11, 3, 5, 59, 0).unwrap()),
+            start
+        );
+    }
+}
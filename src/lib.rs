@@ -0,0 +1,58 @@
+//! Covariance, correlation and volatility of market data.
+//!
+//! The HTTP server in `main.rs` is a thin layer over this library, which can
+//! also be used directly, e.g. against local files through `FileSource`:
+//!
+//! ```no_run
+//! use bitcoin_snp_covariance::data::HistoricalData;
+//! use bitcoin_snp_covariance::estimation::Estimation;
+//! use bitcoin_snp_covariance::interval::Interval;
+//! use bitcoin_snp_covariance::range::DateRange;
+//! use bitcoin_snp_covariance::source::file::{FileSource, FileSourceConfig};
+//! use bitcoin_snp_covariance::source::SourceRegistry;
+//! use bitcoin_snp_covariance::token::Token;
+//! use std::sync::Arc;
+//!
+//! # async fn run() -> Result<(), anyhow::Error> {
+//! let source = FileSource::new(FileSourceConfig::default());
+//! let data = HistoricalData::new(Arc::new(SourceRegistry::from_source(Arc::new(source))), None);
+//!
+//! let range = DateRange::from_query(Some(365), None, None)?;
+//! let volatility = data
+//!     .calculate_realized_volatility(
+//!         Token::from_str("btc").unwrap(),
+//!         range,
+//!         Interval::Daily,
+//!         &[],
+//!         Estimation::Sample,
+//!     )
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+#[macro_use]
+extern crate log;
+
+pub mod alignment;
+pub mod asof;
+pub mod basis;
+pub mod cache;
+pub mod calendar;
+pub mod config;
+pub mod correlation;
+pub mod data;
+pub mod error;
+pub mod estimation;
+pub mod estimator;
+pub mod forecast;
+pub mod interval;
+pub mod limit;
+pub mod metrics;
+pub mod range;
+pub mod regression;
+pub mod request;
+pub mod series;
+pub mod server;
+pub mod source;
+pub mod token;
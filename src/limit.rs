@@ -0,0 +1,84 @@
+use crate::metrics::ProviderMetrics;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+/// Sustained rate and burst size of the requests to one provider.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// Requests per second once the burst is spent.
+    pub per_second: f64,
+    /// Requests that can be sent at once after an idle period.
+    pub burst: u32,
+}
+
+/// Token-bucket rate limiter shared by all the requests to one provider.
+///
+/// Each request takes a token, waiting for it when the bucket is empty.
+/// Tokens are reserved in arrival order, so waiting requests are served first
+/// come, first served.
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+    metrics: Arc<ProviderMetrics>,
+}
+
+/// Available tokens, negative when requests are waiting for future ones.
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter with a full bucket.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The sustained rate and burst size.
+    /// * `metrics` - The metrics of the provider, recording throttled requests.
+    ///
+    /// # Returns
+    ///
+    /// * `RateLimiter` - The rate limiter.
+    pub fn new(limit: RateLimit, metrics: Arc<ProviderMetrics>) -> RateLimiter {
+        RateLimiter {
+            limit,
+            bucket: Mutex::new(Bucket {
+                tokens: limit.burst.max(1) as f64,
+                updated: Instant::now(),
+            }),
+            metrics,
+        }
+    }
+
+    /// Takes a token, waiting until one is available.
+    pub async fn acquire(&self) {
+        let wait = self.reserve();
+        self.metrics.record_request(wait);
+
+        if !wait.is_zero() {
+            debug!("Rate limit reached, waiting {:?}.", wait);
+            sleep(wait).await;
+        }
+    }
+
+    /// Reserves a token and returns how long to wait until it is available.
+    fn reserve(&self) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|err| err.into_inner());
+        let now = Instant::now();
+        let burst = self.limit.burst.max(1) as f64;
+
+        let refilled = now.duration_since(bucket.updated).as_secs_f64() * self.limit.per_second;
+        bucket.tokens = (bucket.tokens + refilled).min(burst);
+        bucket.updated = now;
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.limit.per_second)
+        }
+    }
+}
@@ -2,25 +2,70 @@
 extern crate log;
 use std::env;
 
-use actix_web::{middleware::Logger, App, HttpServer};
-use pretty_env_logger;
-
-mod data;
-mod request;
-mod server;
+use actix_web::{middleware::Logger, web, App, HttpServer};
+use bitcoin_snp_covariance::cache::PriceCache;
+use bitcoin_snp_covariance::config::Config;
+use bitcoin_snp_covariance::data::HistoricalData;
+use bitcoin_snp_covariance::error::ApiError;
+use bitcoin_snp_covariance::server;
+use bitcoin_snp_covariance::source::SourceRegistry;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     env::set_var("RUST_LOG", "debug");
     env::set_var("RUST_BACKTRACE", "1");
     pretty_env_logger::init();
+    dotenv::dotenv().ok();
+
+    let config = Config::from_env();
+    let cache = match &config.cache_path {
+        Some(path) => match PriceCache::open(path, config.cache_ttl) {
+            Ok(cache) => Some(Arc::new(cache)),
+            Err(err) => {
+                error!("Failed to open the price cache at {:?}: {}", path, err);
+                None
+            }
+        },
+        None => None,
+    };
+
+    // One client for every provider, so connections are pooled across requests
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(5))
+        .build()
+        .map_err(std::io::Error::other)?;
+    let sources = Arc::new(SourceRegistry::new(&config, &client));
+
+    // Open provider sessions in the background, fetches open them lazily otherwise
+    let prepared_sources = sources.clone();
+    actix_web::rt::spawn(async move { prepared_sources.prepare().await });
+
+    let historical_data =
+        web::Data::new(HistoricalData::new(sources, cache).with_crypto_close(config.crypto_close));
 
     HttpServer::new(move || {
         let logger = Logger::default();
         App::new()
             .wrap(logger)
+            .app_data(historical_data.clone())
+            // Malformed query strings get the same JSON error bodies as the handlers
+            .app_data(
+                web::QueryConfig::default()
+                    .error_handler(|err, _| ApiError::validation(err.to_string()).into()),
+            )
             .service(server::get_covariance)
+            .service(server::get_matrix)
+            .service(server::get_beta)
+            .service(server::get_cross_correlation)
             .service(server::get_volatility)
+            .service(server::get_volatility_forecast)
+            .service(server::get_rolling_correlation)
+            .service(server::get_rolling_volatility)
+            .service(server::get_metrics)
+            .service(server::get_cache)
+            .service(server::delete_cache)
     })
     .bind("127.0.0.1:8080")?
     .run()
@@ -0,0 +1,65 @@
+use crate::source::SourceKind;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Counters of the fetches and upstream requests of one provider.
+#[derive(Debug, Default)]
+pub struct ProviderMetrics {
+    fetches: AtomicU64,
+    coalesced: AtomicU64,
+    requests: AtomicU64,
+    throttled: AtomicU64,
+    throttled_wait_ms: AtomicU64,
+}
+
+/// Point-in-time copy of the counters of one provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsSnapshot {
+    pub source: SourceKind,
+    /// Fetches sent to the provider.
+    pub fetches: u64,
+    /// Fetches served by joining an identical fetch already in flight.
+    pub coalesced: u64,
+    /// HTTP requests sent to the provider, retries included.
+    pub requests: u64,
+    /// Requests delayed by the rate limiter.
+    pub throttled: u64,
+    /// Total time requests waited for the rate limiter.
+    pub throttled_wait_ms: u64,
+}
+
+impl ProviderMetrics {
+    /// Records a fetch sent to the provider.
+    pub fn record_fetch(&self) {
+        self.fetches.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a fetch that joined one already in flight.
+    pub fn record_coalesced(&self) {
+        self.coalesced.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records an HTTP request, and the time it waited if it was throttled.
+    pub fn record_request(&self, wait: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+
+        if !wait.is_zero() {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+            self.throttled_wait_ms
+                .fetch_add(wait.as_millis() as u64, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns a copy of the counters.
+    pub fn snapshot(&self, source: SourceKind) -> MetricsSnapshot {
+        MetricsSnapshot {
+            source,
+            fetches: self.fetches.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+            requests: self.requests.load(Ordering::Relaxed),
+            throttled: self.throttled.load(Ordering::Relaxed),
+            throttled_wait_ms: self.throttled_wait_ms.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, SubsecRound, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Default lookback used when neither a window nor a start date is requested.
+pub const DEFAULT_LOOKBACK_DAYS: u32 = 365;
+
+/// Time range bounding a historical data request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DateRange {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl DateRange {
+    /// Builds a range from the optional request parameters.
+    ///
+    /// `start` and `end` accept either a plain ISO-8601 date (`2024-03-01`) or a
+    /// full RFC 3339 timestamp. A missing `end` defaults to now, and a missing
+    /// `start` is derived from `lookback` (defaulting to one year).
+    ///
+    /// # Arguments
+    ///
+    /// * `lookback` - Optional lookback window in days, exclusive with `start`.
+    /// * `start` - Optional start of the range.
+    /// * `end` - Optional end of the range.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DateRange, anyhow::Error>` - The validated range, or an error if it is inverted or in the future.
+    pub fn from_query(
+        lookback: Option<u32>,
+        start: Option<&str>,
+        end: Option<&str>,
+    ) -> Result<DateRange, anyhow::Error> {
+        // Whole seconds, so identical queries sent together share their range
+        let now = Utc::now().trunc_subsecs(0);
+
+        let end = match end {
+            Some(end) => Self::parse_bound(end, true, now)?,
+            None => now,
+        };
+
+        let start = match (start, lookback) {
+            (Some(_), Some(_)) => {
+                return Err(anyhow!(
+                    "The parameters <lookback> and <start> cannot be used together."
+                ))
+            }
+            (Some(start), None) => Self::parse_bound(start, false, now)?,
+            (None, Some(0)) => return Err(anyhow!("The lookback window must be at least 1 day.")),
+            (None, lookback) => {
+                let lookback = lookback.unwrap_or(DEFAULT_LOOKBACK_DAYS);
+                end.checked_sub_signed(Duration::days(lookback as i64))
+                    .ok_or_else(|| {
+                        anyhow!("The lookback window <{}> days is too long.", lookback)
+                    })?
+            }
+        };
+
+        if start > now || end > now {
+            return Err(anyhow!(
+                "The requested range <{} - {}> is in the future.",
+                start.to_rfc3339(),
+                end.to_rfc3339()
+            ));
+        }
+
+        if start >= end {
+            return Err(anyhow!(
+                "The start date <{}> must be before the end date <{}>.",
+                start.to_rfc3339(),
+                end.to_rfc3339()
+            ));
+        }
+
+        Ok(DateRange { start, end })
+    }
+
+    /// Parses a single range bound.
+    ///
+    /// Plain dates resolve to the start of the day, or to the end of the day when
+    /// `end_of_day` is set (clamped to `now` for the current day).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The raw date or timestamp string.
+    /// * `end_of_day` - Whether a plain date should resolve to the end of the day.
+    /// * `now` - The current time.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DateTime<Utc>, anyhow::Error>` - The parsed timestamp or an error.
+    fn parse_bound(
+        value: &str,
+        end_of_day: bool,
+        now: DateTime<Utc>,
+    ) -> Result<DateTime<Utc>, anyhow::Error> {
+        if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+            return Ok(timestamp.with_timezone(&Utc));
+        }
+
+        let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+            anyhow!(
+                "Invalid date <{}>, expected ISO-8601 (YYYY-MM-DD or RFC 3339).",
+                value
+            )
+        })?;
+
+        if !end_of_day {
+            return Ok(date.and_time(NaiveTime::MIN).and_utc());
+        }
+
+        if date == now.date_naive() {
+            Ok(now)
+        } else {
+            Ok(date
+                .and_hms_opt(23, 59, 59)
+                .expect("23:59:59 is a valid time")
+                .and_utc())
+        }
+    }
+}
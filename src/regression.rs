@@ -0,0 +1,325 @@
+use crate::error::InsufficientData;
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use statrs::distribution::{ContinuousCDF, FisherSnedecor};
+
+/// Ordinary least squares fit of `y = alpha + beta * x + e`.
+///
+/// Every statistic is per bar, in the units of the series it was fitted on.
+#[derive(Debug, Clone, Copy)]
+pub struct Regression {
+    pub observations: usize,
+    pub alpha: f64,
+    pub beta: f64,
+    pub alpha_standard_error: f64,
+    pub beta_standard_error: f64,
+    /// Share of the variance of `y` explained by `x`.
+    pub r_squared: f64,
+    /// Variance of the residuals, over `n - 2` degrees of freedom.
+    pub residual_variance: f64,
+}
+
+impl Regression {
+    /// Fewest observations a regression is fitted on, leaving a degree of freedom for the residuals.
+    pub const MIN_OBSERVATIONS: usize = 3;
+
+    /// Fits a regression of a dependent series on an explanatory one.
+    ///
+    /// # Arguments
+    ///
+    /// * `y` - The dependent series.
+    /// * `x` - The explanatory series, aligned with the dependent one.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Regression, anyhow::Error>` - Result containing the fitted regression, or an error.
+    pub fn fit(y: &[f64], x: &[f64]) -> Result<Regression, anyhow::Error> {
+        if y.len() != x.len() {
+            return Err(anyhow!(
+                "The series lengths <{}> and <{}> are not equal.",
+                y.len(),
+                x.len()
+            ));
+        }
+
+        if y.len() < Self::MIN_OBSERVATIONS {
+            return Err(InsufficientData(format!(
+                "Not enough values <{}> to fit a regression, at least {} are required.",
+                y.len(),
+                Self::MIN_OBSERVATIONS
+            ))
+            .into());
+        }
+
+        let n = y.len() as f64;
+        let mean_x = x.iter().sum::<f64>() / n;
+        let mean_y = y.iter().sum::<f64>() / n;
+
+        let sxx = x.iter().map(|v| (v - mean_x).powi(2)).sum::<f64>();
+        let syy = y.iter().map(|v| (v - mean_y).powi(2)).sum::<f64>();
+        let sxy = x
+            .iter()
+            .zip(y)
+            .map(|(vx, vy)| (vx - mean_x) * (vy - mean_y))
+            .sum::<f64>();
+
+        if sxx == 0.0 {
+            return Err(InsufficientData(
+                "The explanatory series is constant, its beta is undefined.".to_string(),
+            )
+            .into());
+        }
+
+        let beta = sxy / sxx;
+        let alpha = mean_y - beta * mean_x;
+
+        // Residual sum of squares, floored as rounding can push perfect fits below zero
+        let residual_sum = (syy - beta * sxy).max(0.0);
+        let residual_variance = residual_sum / (n - 2.0);
+
+        Ok(Regression {
+            observations: y.len(),
+            alpha,
+            beta,
+            alpha_standard_error: (residual_variance * (1.0 / n + mean_x.powi(2) / sxx)).sqrt(),
+            beta_standard_error: (residual_variance / sxx).sqrt(),
+            r_squared: if syy > 0.0 {
+                1.0 - residual_sum / syy
+            } else {
+                f64::NAN
+            },
+            residual_variance,
+        })
+    }
+}
+
+/// F-test of whether the lagged values of a series help predict another.
+///
+/// The effect is regressed on its own lags (restricted model), then on its
+/// own lags and the lags of the cause (unrestricted model). A small p-value
+/// rejects the hypothesis that the cause does not Granger-cause the effect.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GrangerTest {
+    pub lags: usize,
+    pub observations: usize,
+    pub f_statistic: f64,
+    /// Numerator and denominator degrees of freedom of the F-statistic.
+    pub degrees_of_freedom: [f64; 2],
+    pub p_value: f64,
+}
+
+impl GrangerTest {
+    /// Tests whether a cause series Granger-causes an effect series.
+    ///
+    /// # Arguments
+    ///
+    /// * `effect` - The series being predicted.
+    /// * `cause` - The series whose lags may predict it, aligned with the effect.
+    /// * `lags` - The number of lags of both series in the models.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<GrangerTest, anyhow::Error>` - Result containing the test, or an error.
+    pub fn run(effect: &[f64], cause: &[f64], lags: usize) -> Result<GrangerTest, anyhow::Error> {
+        if effect.len() != cause.len() {
+            return Err(anyhow!(
+                "The series lengths <{}> and <{}> are not equal.",
+                effect.len(),
+                cause.len()
+            ));
+        }
+
+        if lags == 0 {
+            return Err(anyhow!("The Granger test needs at least one lag."));
+        }
+
+        // The first `lags` values only feed the lags of the others
+        let observations = effect.len().saturating_sub(lags);
+        let parameters = 2 * lags + 1;
+
+        if observations <= parameters {
+            return Err(InsufficientData(format!(
+                "Not enough values <{}> for a Granger test with {} lags, more than {} are required.",
+                effect.len(),
+                lags,
+                lags + parameters
+            ))
+            .into());
+        }
+
+        let y = &effect[lags..];
+        let lagged = |series: &[f64], lag: usize| series[lags - lag..series.len() - lag].to_vec();
+
+        let mut regressors: Vec<Vec<f64>> = (1..=lags).map(|lag| lagged(effect, lag)).collect();
+        let restricted = Self::residual_sum_of_squares(y, &regressors)?;
+
+        regressors.extend((1..=lags).map(|lag| lagged(cause, lag)));
+        let unrestricted = Self::residual_sum_of_squares(y, &regressors)?;
+
+        let degrees_of_freedom = [lags as f64, (observations - parameters) as f64];
+        let f_statistic = ((restricted - unrestricted) / degrees_of_freedom[0]).max(0.0)
+            / (unrestricted / degrees_of_freedom[1]);
+        let p_value =
+            FisherSnedecor::new(degrees_of_freedom[0], degrees_of_freedom[1])?.sf(f_statistic);
+
+        Ok(GrangerTest {
+            lags,
+            observations,
+            f_statistic,
+            degrees_of_freedom,
+            p_value,
+        })
+    }
+
+    /// Fits `y` on an intercept and regressors by least squares.
+    ///
+    /// The normal equations are solved by Gaussian elimination with partial pivoting.
+    ///
+    /// # Arguments
+    ///
+    /// * `y` - The dependent series.
+    /// * `regressors` - The explanatory series, each aligned with the dependent one.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<f64, anyhow::Error>` - Result containing the residual sum of squares, or an error.
+    fn residual_sum_of_squares(y: &[f64], regressors: &[Vec<f64>]) -> Result<f64, anyhow::Error> {
+        let k = regressors.len() + 1;
+        let row = |t: usize| std::iter::once(1.0).chain(regressors.iter().map(move |x| x[t]));
+
+        // Augmented matrix `[X'X | X'y]`
+        let mut system = vec![vec![0.0; k + 1]; k];
+        for (t, target) in y.iter().enumerate() {
+            let values: Vec<f64> = row(t).collect();
+            for i in 0..k {
+                for j in 0..k {
+                    system[i][j] += values[i] * values[j];
+                }
+                system[i][k] += values[i] * target;
+            }
+        }
+
+        for column in 0..k {
+            let pivot = (column..k)
+                .max_by(|&a, &b| system[a][column].abs().total_cmp(&system[b][column].abs()))
+                .unwrap_or(column);
+
+            if system[pivot][column].abs() < f64::EPSILON {
+                return Err(InsufficientData(
+                    "The lagged series are collinear, the regression is undefined.".to_string(),
+                )
+                .into());
+            }
+
+            system.swap(column, pivot);
+            let pivot_equation = system[column].clone();
+            for (i, equation) in system.iter_mut().enumerate() {
+                if i != column {
+                    let factor = equation[column] / pivot_equation[column];
+                    for (value, p) in equation.iter_mut().zip(&pivot_equation).skip(column) {
+                        *value -= factor * p;
+                    }
+                }
+            }
+        }
+
+        let coefficients: Vec<f64> = (0..k).map(|i| system[i][k] / system[i][i]).collect();
+
+        Ok(y.iter()
+            .enumerate()
+            .map(|(t, target)| {
+                let fitted = row(t).zip(&coefficients).map(|(x, c)| x * c).sum::<f64>();
+                (target - fitted).powi(2)
+            })
+            .sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GrangerTest, Regression};
+
+    /// Pseudo-random noise in `[-0.5, 0.5)` from a seeded linear congruential generator.
+    fn noise(len: usize, seed: u64) -> Vec<f64> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+            })
+            .collect()
+    }
+
+    /// Residuals of a simple regression of `y` on `x`.
+    fn residuals(y: &[f64], x: &[f64]) -> Vec<f64> {
+        let fit = Regression::fit(y, x).unwrap();
+        y.iter()
+            .zip(x)
+            .map(|(vy, vx)| vy - fit.alpha - fit.beta * vx)
+            .collect()
+    }
+
+    #[test]
+    fn regression_fits_a_known_line() {
+        let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let y = [2.9, 5.1, 7.0, 9.1, 10.9];
+        let fit = Regression::fit(&y, &x).unwrap();
+
+        assert!((fit.beta - 2.0).abs() < 1e-12);
+        assert!((fit.alpha - 1.0).abs() < 1e-12);
+        assert!((fit.residual_variance - 0.04 / 3.0).abs() < 1e-12);
+        assert!((fit.beta_standard_error - (0.04f64 / 30.0).sqrt()).abs() < 1e-12);
+        assert!((fit.r_squared - (1.0 - 0.04 / 40.04)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn regression_rejects_a_constant_regressor() {
+        assert!(Regression::fit(&[1.0, 2.0, 3.0], &[1.0, 1.0, 1.0]).is_err());
+        assert!(Regression::fit(&[1.0, 2.0], &[1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn granger_f_statistic_matches_partialled_out_regression() {
+        // The effect follows the cause with a lag of one bar
+        let cause = noise(200, 7);
+        let shocks = noise(200, 11);
+        let effect: Vec<f64> = (0..200)
+            .map(|t| match t {
+                0 => shocks[0],
+                _ => 0.8 * cause[t - 1] + 0.2 * shocks[t],
+            })
+            .collect();
+
+        let test = GrangerTest::run(&effect, &cause, 1).unwrap();
+
+        // With one lag, the cause only explains what the own lag of the effect leaves
+        let (y, own_lag, cause_lag) = (&effect[1..], &effect[..199], &cause[..199]);
+        let effect_residuals = residuals(y, own_lag);
+        let cause_residuals = residuals(cause_lag, own_lag);
+        let partial = Regression::fit(&effect_residuals, &cause_residuals).unwrap();
+
+        let restricted = effect_residuals.iter().map(|e| e.powi(2)).sum::<f64>();
+        let unrestricted = partial.residual_variance * 197.0;
+        let expected = (restricted - unrestricted) / (unrestricted / 196.0);
+
+        assert_eq!(test.observations, 199);
+        assert_eq!(test.degrees_of_freedom, [1.0, 196.0]);
+        assert!((test.f_statistic / expected - 1.0).abs() < 1e-9);
+        assert!(test.p_value < 1e-10);
+
+        // The effect does not lead the cause
+        let reverse = GrangerTest::run(&cause, &effect, 1).unwrap();
+        assert!(reverse.p_value > 0.01, "p-value {}", reverse.p_value);
+    }
+
+    #[test]
+    fn granger_needs_more_values_than_parameters() {
+        let values = noise(8, 3);
+
+        assert!(GrangerTest::run(&values, &values, 0).is_err());
+        assert!(GrangerTest::run(&values, &noise(8, 5), 3).is_err());
+        assert!(GrangerTest::run(&values, &noise(8, 5), 2).is_ok());
+    }
+}
@@ -1,107 +1,493 @@
 // ==============================================================================================
-use anyhow::anyhow;
-use reqwest::{header::HeaderMap, Method, StatusCode};
+use crate::limit::RateLimiter;
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::{Method, Response, StatusCode};
+use serde::de::DeserializeOwned;
 use serde_json::Value;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
 // ==============================================================================================
 
-pub struct Request;
+/// Default number of attempts of a request, including the first one.
+const DEFAULT_ATTEMPTS: u32 = 4;
+
+/// Default timeout of a single attempt.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delay before the first retry, doubled on every following one.
+const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest delay between two attempts, including delays asked by `Retry-After`.
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Errors of an HTTP request, after retries.
+#[derive(Debug, thiserror::Error)]
+pub enum RequestError {
+    #[error("🚨 Invalid URL <{url}>: {message}")]
+    Url { url: String, message: String },
+    #[error("🚨 URL: {url} | Transport error: {source}")]
+    Transport { url: String, source: reqwest::Error },
+    #[error("🚨 URL: {url} | Timed out after {timeout:?}.")]
+    Timeout { url: String, timeout: Duration },
+    #[error("🚨 URL: {url} Status: {status} | Can't process request.")]
+    Status {
+        url: String,
+        status: StatusCode,
+        /// The response body, e.g. a provider error payload.
+        body: String,
+    },
+    #[error("🚨 URL: {url} | Failed to decode the response: {message}")]
+    Decode { url: String, message: String },
+}
+
+impl RequestError {
+    /// Returns whether the server rejected the credentials of the request (401 or 403).
+    pub fn is_unauthorized(&self) -> bool {
+        matches!(
+            self,
+            RequestError::Status { status, .. }
+                if *status == StatusCode::UNAUTHORIZED || *status == StatusCode::FORBIDDEN
+        )
+    }
+
+    /// Returns whether the request may succeed if sent again.
+    fn is_retryable(&self) -> bool {
+        match self {
+            RequestError::Transport { source, .. } => !source.is_builder(),
+            RequestError::Timeout { .. } => true,
+            RequestError::Status { status, .. } => matches!(
+                *status,
+                StatusCode::REQUEST_TIMEOUT
+                    | StatusCode::TOO_MANY_REQUESTS
+                    | StatusCode::INTERNAL_SERVER_ERROR
+                    | StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            RequestError::Url { .. } | RequestError::Decode { .. } => false,
+        }
+    }
+}
+
+/// Attempts, timeout and backoff of the requests to one provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of attempts, including the first one.
+    pub attempts: u32,
+    /// Timeout of a single attempt.
+    pub timeout: Duration,
+    /// Delay before the first retry, doubled on every following one.
+    pub base_delay: Duration,
+    /// Longest delay between two attempts.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: DEFAULT_ATTEMPTS,
+            timeout: DEFAULT_TIMEOUT,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the delay before a retry, exponential with jitter.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - The attempt that just failed, starting at 1.
+    ///
+    /// # Returns
+    ///
+    /// * `Duration` - A random delay between half and all of the exponential delay.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+
+        exponential.mul_f64(0.5 + fastrand::f64() * 0.5)
+    }
+}
+
+/// HTTP requests to one provider, sharing the connection pool of a long-lived client.
+#[derive(Debug, Clone)]
+pub struct Request {
+    client: reqwest::Client,
+    policy: RetryPolicy,
+    limiter: Option<Arc<RateLimiter>>,
+}
 
 impl Request {
-    /// Processes an HTTP request with the given method, URL, body, and headers.
-    /// Retries the request if it fails, up to a maximum number of attempts.
+    /// Creates the requests of a provider.
     ///
     /// # Arguments
     ///
-    /// * `method` - The HTTP method to use for the request (GET, POST, etc.).
-    /// * `url` - The URL to send the request to.
-    /// * `body` - An optional JSON body to include in the request (for POST requests).
-    /// * `headers` - Optional HTTP headers to include in the request.
+    /// * `client` - The shared HTTP client; clones share its connection pool.
+    /// * `policy` - The attempts, timeout and backoff of the provider.
+    ///
+    /// # Returns
+    ///
+    /// * `Request` - The requests.
+    pub fn new(client: reqwest::Client, policy: RetryPolicy) -> Request {
+        Request {
+            client,
+            policy,
+            limiter: None,
+        }
+    }
+
+    /// Limits the rate of the requests, sharing the limiter with every clone.
+    ///
+    /// # Arguments
+    ///
+    /// * `limiter` - The rate limiter of the provider.
+    ///
+    /// # Returns
+    ///
+    /// * `Request` - The rate-limited requests.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Request {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Waits for the rate limiter of the provider, if any, before a request is sent.
+    pub async fn throttle(&self) {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire().await;
+        }
+    }
+
+    /// Returns the shared HTTP client.
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    /// Returns the retry policy of the provider.
+    pub fn policy(&self) -> RetryPolicy {
+        self.policy
+    }
+
+    /// Starts building a call to a URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method of the call (GET, POST, PUT, DELETE, etc.).
+    /// * `url` - The URL of the call, query parameters can be added to it.
+    ///
+    /// # Returns
+    ///
+    /// * `Call` - The call, using the retry policy of the provider unless overridden.
+    pub fn call<S: Into<String>>(&self, method: Method, url: S) -> Call<'_> {
+        Call {
+            request: self,
+            method,
+            url: url.into(),
+            query: Vec::new(),
+            headers: HeaderMap::new(),
+            body: None,
+            policy: self.policy,
+        }
+    }
+
+    /// Classifies a reqwest error into a timeout or transport error.
+    fn classify(url: &str, err: reqwest::Error, policy: RetryPolicy) -> RequestError {
+        if err.is_timeout() {
+            RequestError::Timeout {
+                url: url.to_string(),
+                timeout: policy.timeout,
+            }
+        } else if err.is_decode() || err.is_body() {
+            RequestError::Decode {
+                url: url.to_string(),
+                message: err.to_string(),
+            }
+        } else {
+            RequestError::Transport {
+                url: url.to_string(),
+                source: err.without_url(),
+            }
+        }
+    }
+
+    /// Reads the `Retry-After` header, in seconds or as an HTTP date.
+    fn retry_after(res: &Response) -> Option<Duration> {
+        let value = res
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)?
+            .to_str()
+            .ok()?
+            .trim();
+
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+
+        let date = DateTime::parse_from_rfc2822(value).ok()?;
+        (date.with_timezone(&Utc) - Utc::now()).to_std().ok()
+    }
+}
+
+/// Body of a call.
+#[derive(Debug, Clone)]
+pub enum Body {
+    /// Serialized as JSON, with an `application/json` content type.
+    Json(Value),
+    /// URL-encoded, with an `application/x-www-form-urlencoded` content type.
+    Form(Vec<(String, String)>),
+    /// Sent as is, with a `text/plain` content type unless one is set.
+    Text(String),
+    /// Sent as is, with an `application/octet-stream` content type unless one is set.
+    Bytes(Vec<u8>),
+}
+
+/// A single HTTP call, built from a `Request` and sent by one of its decoding methods.
+///
+/// Retries follow the policy of the provider; calls that must not be repeated,
+/// like non-idempotent POSTs, can disable them with `attempts(1)`.
+#[derive(Debug, Clone)]
+pub struct Call<'a> {
+    request: &'a Request,
+    method: Method,
+    url: String,
+    query: Vec<(String, String)>,
+    headers: HeaderMap,
+    body: Option<Body>,
+    policy: RetryPolicy,
+}
+
+impl Call<'_> {
+    /// Appends a query parameter, URL-encoded when sent.
+    pub fn query<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.query.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Appends several query parameters, URL-encoded when sent.
+    pub fn queries<I, K, V>(mut self, params: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: ToString,
+        V: ToString,
+    {
+        self.query.extend(
+            params
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string())),
+        );
+        self
+    }
+
+    /// Sets a header, replacing any previous value.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Sets several headers, replacing any previous values.
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Sends a JSON body.
+    pub fn json_body(mut self, body: Value) -> Self {
+        self.body = Some(Body::Json(body));
+        self
+    }
+
+    /// Sends a URL-encoded form body.
+    pub fn form<I, K, V>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: ToString,
+        V: ToString,
+    {
+        let fields = fields
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        self.body = Some(Body::Form(fields));
+        self
+    }
+
+    /// Sends any body.
+    pub fn body(mut self, body: Body) -> Self {
+        self.body = Some(body);
+        self
+    }
+
+    /// Overrides the timeout of each attempt of this call.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.policy.timeout = timeout;
+        self
+    }
+
+    /// Overrides the number of attempts of this call, at least one.
+    pub fn attempts(mut self, attempts: u32) -> Self {
+        self.policy.attempts = attempts.max(1);
+        self
+    }
+
+    /// Overrides the whole retry policy of this call.
+    pub fn policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sends the call and decodes the response body as JSON.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<T, RequestError>` - The decoded body, or a `Decode` error if it does not match `T`.
+    pub async fn json<T: DeserializeOwned>(self) -> Result<T, RequestError> {
+        let (url, bytes) = self.send_and_read().await?;
+
+        serde_json::from_slice(&bytes).map_err(|err| RequestError::Decode {
+            url,
+            message: err.to_string(),
+        })
+    }
+
+    /// Sends the call and returns the response body as text.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, RequestError>` - The body, or a `Decode` error if it is not UTF-8.
+    pub async fn text(self) -> Result<String, RequestError> {
+        let (url, bytes) = self.send_and_read().await?;
+
+        String::from_utf8(bytes).map_err(|err| RequestError::Decode {
+            url,
+            message: err.to_string(),
+        })
+    }
+
+    /// Sends the call and returns the raw response body.
     ///
     /// # Returns
     ///
-    /// A `Result` containing the JSON response body if the request
-    /// is successful, or an `anyhow::Error` if it fails.
+    /// * `Result<Vec<u8>, RequestError>` - The body or an error.
+    pub async fn bytes(self) -> Result<Vec<u8>, RequestError> {
+        let (_, bytes) = self.send_and_read().await?;
+
+        Ok(bytes)
+    }
+
+    /// Sends the call and reads the whole response body.
+    async fn send_and_read(self) -> Result<(String, Vec<u8>), RequestError> {
+        let policy = self.policy;
+        let res = self.send().await?;
+        let url = res.url().to_string();
+        let bytes = res
+            .bytes()
+            .await
+            .map_err(|err| Request::classify(&url, err, policy))?;
+
+        Ok((url, bytes.to_vec()))
+    }
+
+    /// Sends the call, retrying it according to its policy.
     ///
-    /// # Errors
+    /// Transport errors, timeouts and 408/429/5xx responses are retried with
+    /// exponential backoff and jitter, waiting at least as long as the
+    /// `Retry-After` header asks. Other responses, including 404, fail at once
+    /// with their body so callers can read provider error payloads.
     ///
-    /// This function returns an error if the input URL cannot be parsed or
-    /// if the request method is not supported.
+    /// # Returns
     ///
-    /// If the request fails after the maximum number
-    /// of attempts, an error is also returned.
-    pub async fn process_request<S: AsRef<str>>(
-        method: Method,
-        url: S,
-        headers: Option<HeaderMap>,
-        body: Option<Value>,
-    ) -> Result<Value, anyhow::Error> {
-        let attempts_limit = 15;
+    /// A `Result` containing the successful response, or the last
+    /// `RequestError` once the attempts are exhausted.
+    async fn send(self) -> Result<Response, RequestError> {
+        let mut url = reqwest::Url::parse(&self.url).map_err(|err| RequestError::Url {
+            url: self.url.clone(),
+            message: err.to_string(),
+        })?;
+
+        if !self.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.query);
+        }
+
         let mut attempt = 1;
-        let wait_delay = Duration::from_secs_f64(1.5);
-
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(5))
-            .build()?;
-
-        let url = reqwest::Url::parse(url.as_ref())?;
-        let headers = headers.unwrap_or_else(HeaderMap::new);
-
-        while attempt <= attempts_limit {
-            let request = match method.clone() {
-                Method::GET => client
-                    .request(method.clone(), url.clone())
-                    .headers(headers.clone()),
-                Method::POST => client
-                    .request(method.clone(), url.clone())
-                    .json(&body)
-                    .headers(headers.clone()),
-                _ => return Err(anyhow!("The method <{}> is not supported.", method)),
+
+        loop {
+            self.request.throttle().await;
+
+            let request = self
+                .request
+                .client
+                .request(self.method.clone(), url.clone())
+                .headers(self.headers.clone())
+                .timeout(self.policy.timeout);
+
+            let request = match &self.body {
+                Some(Body::Json(body)) => request.json(body),
+                Some(Body::Form(fields)) => request.form(fields),
+                Some(Body::Text(text)) => self
+                    .default_content_type(request, "text/plain; charset=utf-8")
+                    .body(text.clone()),
+                Some(Body::Bytes(bytes)) => self
+                    .default_content_type(request, "application/octet-stream")
+                    .body(bytes.clone()),
+                None => request,
             };
 
-            match request.send().await {
-                Ok(res) => match res.status() {
-                    StatusCode::OK => {
-                        let json: Value = res.json().await?;
-                        return Ok(json);
-                    }
-
-                    StatusCode::NOT_FOUND | StatusCode::TOO_MANY_REQUESTS => {
-                        error!("{:?}", res.text().await?);
-                        sleep(wait_delay).await;
-                        attempt += 1;
-                        continue;
-                    }
-
-                    StatusCode::GATEWAY_TIMEOUT => {
-                        return Err(anyhow!(
-                            "🚨 URL: {} Status: {} | Can't process request.",
-                            res.url().to_string(),
-                            res.status()
-                        ))
-                    }
-
-                    _ => {
-                        warn!(
-                            "Critical response error. URL: {} Status: {} | {:#?}",
-                            res.url().to_string(),
-                            res.status(),
-                            res.text().await?
-                        );
-
-                        sleep(wait_delay).await;
-                        attempt += 1;
-                        continue;
-                    }
-                },
-                Err(_) => {
-                    attempt += 1;
-                    continue;
+            let (err, retry_after) = match request.send().await {
+                Ok(res) if res.status().is_success() => return Ok(res),
+                Ok(res) => {
+                    let status = res.status();
+                    let retry_after = Request::retry_after(&res);
+                    let body = res.text().await.unwrap_or_default();
+
+                    (
+                        RequestError::Status {
+                            url: url.to_string(),
+                            status,
+                            body,
+                        },
+                        retry_after,
+                    )
                 }
+                Err(err) => (Request::classify(url.as_str(), err, self.policy), None),
+            };
+
+            if !err.is_retryable() || attempt >= self.policy.attempts {
+                return Err(err);
             }
+
+            // A server asking for more than the longest delay will not be waited for
+            let delay = match retry_after {
+                Some(retry_after) if retry_after > self.policy.max_delay => return Err(err),
+                Some(retry_after) => retry_after.max(self.policy.backoff(attempt)),
+                None => self.policy.backoff(attempt),
+            };
+
+            warn!(
+                "{} Retrying in {:?} (attempt {}/{}).",
+                err, delay, attempt, self.policy.attempts
+            );
+
+            sleep(delay).await;
+            attempt += 1;
         }
+    }
 
-        return Err(anyhow!("🚨 Attempts reached. Check URL: {}", url.as_str()));
+    /// Sets a content type on a request unless the call sets its own.
+    fn default_content_type(
+        &self,
+        request: reqwest::RequestBuilder,
+        content_type: &'static str,
+    ) -> reqwest::RequestBuilder {
+        if self.headers.contains_key(CONTENT_TYPE) {
+            request
+        } else {
+            request.header(CONTENT_TYPE, content_type)
+        }
     }
 }
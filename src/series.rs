@@ -0,0 +1,421 @@
+use crate::calendar::TradingCalendar;
+use crate::interval::Interval;
+use crate::range::DateRange;
+use crate::token::Token;
+use chrono::{DateTime, Duration, Months, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A single OHLCV bar.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bar {
+    pub date: NaiveDateTime,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: Option<f64>,
+    pub adjclose: Option<f64>,
+}
+
+impl Bar {
+    /// Returns whether the bar only has a close, its open, high and low being filled with it.
+    pub fn is_close_only(&self) -> bool {
+        self.open == self.close && self.high == self.close && self.low == self.close
+    }
+}
+
+/// Data-quality report of a fetched price series.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataQuality {
+    pub symbol: String,
+    /// Bars (or single-price observations) returned by the provider.
+    pub total_bars: usize,
+    /// Bars kept after dropping nulls and duplicates.
+    pub valid_bars: usize,
+    /// Bars or observations dropped because a price was null.
+    pub null_bars: usize,
+    /// Bars sharing their bar start with an earlier bar; the latest one is kept.
+    pub duplicate_bars: usize,
+    /// Places where at least one expected bar is missing between two valid bars.
+    pub gaps: usize,
+}
+
+/// Chronologically ordered bars of a token with their data-quality report.
+#[derive(Debug, Clone)]
+pub struct PriceSeries {
+    pub bars: BTreeMap<NaiveDateTime, Bar>,
+    pub quality: DataQuality,
+}
+
+impl PriceSeries {
+    /// Builds a series from the raw bars returned by a provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token the bars belong to.
+    /// * `interval` - The bar interval, used to detect gaps.
+    /// * `raw_bars` - The bars in provider order, `None` where a bar had null prices.
+    ///
+    /// # Returns
+    ///
+    /// * `PriceSeries` - The deduplicated series and its data-quality report.
+    pub fn from_raw_bars(
+        token: &Token,
+        interval: Interval,
+        raw_bars: Vec<Option<Bar>>,
+    ) -> PriceSeries {
+        let total_bars = raw_bars.len();
+        let mut null_bars = 0;
+        let mut duplicate_bars = 0;
+        let mut bars = BTreeMap::new();
+
+        for raw_bar in raw_bars {
+            match raw_bar {
+                Some(bar) => {
+                    if bars.insert(bar.date, bar).is_some() {
+                        duplicate_bars += 1;
+                    }
+                }
+                None => null_bars += 1,
+            }
+        }
+
+        let gaps = Self::count_gaps(&bars, interval, token.calendar());
+
+        PriceSeries {
+            quality: DataQuality {
+                symbol: token.id().to_string(),
+                total_bars,
+                valid_bars: bars.len(),
+                null_bars,
+                duplicate_bars,
+                gaps,
+            },
+            bars,
+        }
+    }
+
+    /// Builds a series from single-price observations, aggregating them into bars.
+    ///
+    /// Observations falling into the same bar set its open (first), high, low
+    /// and close (last), for providers that only serve one price per timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token the observations belong to.
+    /// * `interval` - The bar interval to aggregate into.
+    /// * `points` - The observations, `None` where the price was null.
+    ///
+    /// # Returns
+    ///
+    /// * `PriceSeries` - The aggregated series and its data-quality report.
+    pub fn from_points(
+        token: &Token,
+        interval: Interval,
+        mut points: Vec<(DateTime<Utc>, Option<f64>)>,
+    ) -> PriceSeries {
+        let total_bars = points.len();
+        let mut null_bars = 0;
+        let mut bars: BTreeMap<NaiveDateTime, Bar> = BTreeMap::new();
+
+        points.sort_by_key(|(timestamp, _)| *timestamp);
+
+        for (timestamp, price) in points {
+            let price = match price {
+                Some(price) => price,
+                None => {
+                    null_bars += 1;
+                    continue;
+                }
+            };
+
+            let date = interval.bar_start(timestamp);
+            bars.entry(date)
+                .and_modify(|bar| {
+                    bar.high = bar.high.max(price);
+                    bar.low = bar.low.min(price);
+                    bar.close = price;
+                })
+                .or_insert(Bar {
+                    date,
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: None,
+                    adjclose: None,
+                });
+        }
+
+        let gaps = Self::count_gaps(&bars, interval, token.calendar());
+
+        PriceSeries {
+            quality: DataQuality {
+                symbol: token.id().to_string(),
+                total_bars,
+                valid_bars: bars.len(),
+                null_bars,
+                duplicate_bars: 0,
+                gaps,
+            },
+            bars,
+        }
+    }
+
+    /// Builds a series from bars of the same or a finer interval, merging them into bars.
+    ///
+    /// Bars falling into the same bar set its open (first), high (max), low
+    /// (min), close and adjusted close (last) and volume (sum). Only bars
+    /// sharing the exact same date are reported as duplicates. Daily and
+    /// coarser bars are dated by their local date in the timezone of the data.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token the bars belong to.
+    /// * `interval` - The bar interval to merge into.
+    /// * `raw_bars` - The bars dated by their exact time in UTC, `None` where a bar had null prices.
+    /// * `timezone` - The timezone the bars are dated in.
+    ///
+    /// # Returns
+    ///
+    /// * `PriceSeries` - The merged series and its data-quality report.
+    pub fn from_finer_bars(
+        token: &Token,
+        interval: Interval,
+        raw_bars: Vec<Option<Bar>>,
+        timezone: Tz,
+    ) -> PriceSeries {
+        let total_bars = raw_bars.len();
+        let null_bars = raw_bars.iter().filter(|bar| bar.is_none()).count();
+
+        // Keep the last bar of each exact date, as `from_raw_bars` does
+        let mut finer: BTreeMap<NaiveDateTime, Bar> = BTreeMap::new();
+        let mut duplicate_bars = 0;
+        for bar in raw_bars.into_iter().flatten() {
+            if finer.insert(bar.date, bar).is_some() {
+                duplicate_bars += 1;
+            }
+        }
+
+        let mut bars: BTreeMap<NaiveDateTime, Bar> = BTreeMap::new();
+        for bar in finer.into_values() {
+            let date = interval.local_bar_start(bar.date.and_utc(), timezone);
+            bars.entry(date)
+                .and_modify(|merged| {
+                    merged.high = merged.high.max(bar.high);
+                    merged.low = merged.low.min(bar.low);
+                    merged.close = bar.close;
+                    merged.adjclose = bar.adjclose;
+                    merged.volume = match (merged.volume, bar.volume) {
+                        (Some(total), Some(volume)) => Some(total + volume),
+                        (total, volume) => total.or(volume),
+                    };
+                })
+                .or_insert(Bar { date, ..bar });
+        }
+
+        let gaps = Self::count_gaps(&bars, interval, token.calendar());
+
+        PriceSeries {
+            quality: DataQuality {
+                symbol: token.id().to_string(),
+                total_bars,
+                valid_bars: bars.len(),
+                null_bars,
+                duplicate_bars,
+                gaps,
+            },
+            bars,
+        }
+    }
+
+    /// Returns the part of the series within a range.
+    ///
+    /// The bar the range starts in is kept, as a fetch of the range returns it
+    /// too. Kept and missing bars are counted again on the trimmed part, while
+    /// the null and duplicate bars dropped upstream stay in the report.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token the bars belong to.
+    /// * `interval` - The bar interval, used to detect gaps.
+    /// * `range` - The range of bars to keep.
+    ///
+    /// # Returns
+    ///
+    /// * `PriceSeries` - The trimmed series.
+    pub fn within(&self, token: &Token, interval: Interval, range: &DateRange) -> PriceSeries {
+        let raw_bars = self
+            .bars
+            .range(interval.bar_start(range.start)..=range.end.naive_utc())
+            .map(|(_, bar)| Some(*bar))
+            .collect();
+
+        let mut series = PriceSeries::from_raw_bars(token, interval, raw_bars);
+        series.quality.total_bars = self.quality.total_bars;
+        series.quality.null_bars = self.quality.null_bars;
+        series.quality.duplicate_bars = self.quality.duplicate_bars;
+
+        series
+    }
+
+    /// Returns whether the series holds no bars.
+    pub fn is_empty(&self) -> bool {
+        self.bars.is_empty()
+    }
+
+    /// Returns the closing prices in chronological order.
+    pub fn closes(&self) -> Vec<f64> {
+        self.bars.values().map(|bar| bar.close).collect()
+    }
+
+    /// Returns the series with its bars moved to new bar starts.
+    ///
+    /// Bars moved onto the same start are merged as duplicates, the latest
+    /// one being kept; null bars and duplicates of the original series stay
+    /// in the data-quality report.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token the bars belong to.
+    /// * `interval` - The bar interval, used to detect gaps.
+    /// * `bar_start` - Maps the start of each bar to its new start.
+    ///
+    /// # Returns
+    ///
+    /// * `PriceSeries` - The redated series.
+    pub fn redated<F: Fn(NaiveDateTime) -> NaiveDateTime>(
+        &self,
+        token: &Token,
+        interval: Interval,
+        bar_start: F,
+    ) -> PriceSeries {
+        let raw_bars = self
+            .bars
+            .values()
+            .map(|bar| {
+                Some(Bar {
+                    date: bar_start(bar.date),
+                    ..*bar
+                })
+            })
+            .collect();
+
+        let mut series = PriceSeries::from_raw_bars(token, interval, raw_bars);
+        series.quality.total_bars = self.quality.total_bars;
+        series.quality.null_bars = self.quality.null_bars;
+        series.quality.duplicate_bars += self.quality.duplicate_bars;
+        series
+    }
+
+    /// Counts the places where bars are missing in a series.
+    ///
+    /// # Arguments
+    ///
+    /// * `bars` - The bars keyed by bar start.
+    /// * `interval` - The bar interval.
+    /// * `calendar` - The trading calendar of the token.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of gaps.
+    fn count_gaps(
+        bars: &BTreeMap<NaiveDateTime, Bar>,
+        interval: Interval,
+        calendar: TradingCalendar,
+    ) -> usize {
+        bars.keys()
+            .zip(bars.keys().skip(1))
+            .filter(|(prev, next)| Self::is_gap(**prev, **next, interval, calendar))
+            .count()
+    }
+
+    /// Returns whether bars are missing between two consecutive bar starts.
+    ///
+    /// Equity series are not expected to trade on weekends, exchange holidays
+    /// or overnight, so those spans are not reported as gaps.
+    ///
+    /// # Arguments
+    ///
+    /// * `prev` - The earlier bar start.
+    /// * `next` - The following bar start.
+    /// * `interval` - The bar interval.
+    /// * `calendar` - The trading calendar of the token.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if at least one expected bar is missing.
+    fn is_gap(
+        prev: NaiveDateTime,
+        next: NaiveDateTime,
+        interval: Interval,
+        calendar: TradingCalendar,
+    ) -> bool {
+        let expected = match (interval, calendar) {
+            (Interval::Hourly, TradingCalendar::Equity) if prev.date() != next.date() => {
+                return false
+            }
+            (Interval::Hourly, _) => prev + Duration::hours(1),
+            (Interval::Daily, TradingCalendar::Equity) => {
+                calendar.next_session(prev.date()).and_time(prev.time())
+            }
+            (Interval::Daily, TradingCalendar::Crypto) => prev + Duration::days(1),
+            (Interval::Weekly, _) => prev + Duration::weeks(1),
+            (Interval::Monthly, _) => prev + Months::new(1),
+        };
+
+        next > expected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bar, PriceSeries};
+    use crate::interval::Interval;
+    use crate::range::DateRange;
+    use crate::token::Token;
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    fn bar(day: u32, close: f64) -> Option<Bar> {
+        Some(Bar {
+            date: NaiveDate::from_ymd_opt(2024, 1, day)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: None,
+            adjclose: None,
+        })
+    }
+
+    #[test]
+    fn within_keeps_the_bar_the_range_starts_in() {
+        let token = Token::from_str("BTC").unwrap();
+        let raw_bars = vec![
+            bar(1, 1.0),
+            None,
+            bar(2, 2.0),
+            bar(3, 3.0),
+            bar(3, 3.5),
+            bar(4, 4.0),
+        ];
+        let series = PriceSeries::from_raw_bars(&token, Interval::Daily, raw_bars);
+
+        let range = DateRange {
+            start: Utc.with_ymd_and_hms(2024, 1, 2, 14, 23, 0).unwrap(),
+            end: Utc.with_ymd_and_hms(2024, 1, 3, 14, 23, 0).unwrap(),
+        };
+        let trimmed = series.within(&token, Interval::Daily, &range);
+
+        assert_eq!(trimmed.closes(), vec![2.0, 3.5]);
+        assert_eq!(trimmed.quality.valid_bars, 2);
+        assert_eq!(trimmed.quality.total_bars, 6);
+        assert_eq!(trimmed.quality.null_bars, 1);
+        assert_eq!(trimmed.quality.duplicate_bars, 1);
+    }
+}
@@ -1,70 +1,712 @@
-use crate::data::{HistoricalData, Token};
-use actix_web::{get, web, HttpResponse, Responder};
+use crate::alignment::Alignment;
+use crate::basis::Basis;
+use crate::cache::PriceCache;
+use crate::correlation::CorrelationMethod;
+use crate::data::{
+    CorrelationOptions, CrossCorrelationLags, HistoricalData, RollingWindow, DEFAULT_CONFIDENCE,
+};
+use crate::error::ApiError;
+use crate::estimation::Estimation;
+use crate::estimator::Estimator;
+use crate::forecast::ForecastModel;
+use crate::interval::Interval;
+use crate::range::DateRange;
+use crate::regression::Regression;
+use crate::source::SourceKind;
+use crate::token::Token;
+use actix_web::{delete, get, web, HttpResponse, Responder};
 use serde::Deserialize;
+use serde_json::json;
+
+/// Maximum number of symbols accepted by the matrix endpoint.
+const MAX_MATRIX_TOKENS: usize = 50;
+
+/// Default number of bars in a rolling window.
+const DEFAULT_ROLLING_WINDOW: usize = 30;
+
+/// Default largest lag, in bars, of the cross-correlation endpoint.
+const DEFAULT_MAX_LAG: usize = 5;
+
+/// Maximum lag, in bars, accepted by the cross-correlation endpoint.
+const MAX_LAG: usize = 60;
+
+/// Default number of bars forecast by the volatility forecast endpoint.
+const DEFAULT_FORECAST_HORIZON: usize = 10;
+
+/// Maximum number of bars forecast by the volatility forecast endpoint.
+const MAX_FORECAST_HORIZON: usize = 365;
 
 #[derive(Deserialize)]
 pub struct CovarianceQuery {
     token_1: Option<String>,
     token_2: Option<String>,
+    lookback: Option<u32>,
+    start: Option<String>,
+    end: Option<String>,
+    interval: Option<String>,
+    basis: Option<String>,
+    half_life: Option<f64>,
+    estimation: Option<String>,
+    method: Option<String>,
+    winsorize: Option<f64>,
+    confidence: Option<f64>,
+    alignment: Option<String>,
+    source: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MatrixQuery {
+    symbols: Option<String>,
+    lookback: Option<u32>,
+    start: Option<String>,
+    end: Option<String>,
+    interval: Option<String>,
+    basis: Option<String>,
+    half_life: Option<f64>,
+    estimation: Option<String>,
+    method: Option<String>,
+    winsorize: Option<f64>,
+    alignment: Option<String>,
+    source: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct BetaQuery {
+    token_1: Option<String>,
+    token_2: Option<String>,
+    lookback: Option<u32>,
+    start: Option<String>,
+    end: Option<String>,
+    interval: Option<String>,
+    basis: Option<String>,
+    window: Option<usize>,
+    step: Option<usize>,
+    alignment: Option<String>,
+    source: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CrossCorrelationQuery {
+    token_1: Option<String>,
+    token_2: Option<String>,
+    lookback: Option<u32>,
+    start: Option<String>,
+    end: Option<String>,
+    interval: Option<String>,
+    basis: Option<String>,
+    max_lag: Option<usize>,
+    granger_lags: Option<usize>,
+    alignment: Option<String>,
+    source: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RollingCorrelationQuery {
+    token_1: Option<String>,
+    token_2: Option<String>,
+    lookback: Option<u32>,
+    start: Option<String>,
+    end: Option<String>,
+    interval: Option<String>,
+    basis: Option<String>,
+    window: Option<usize>,
+    step: Option<usize>,
+    alignment: Option<String>,
+    source: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ForecastQuery {
+    token: Option<String>,
+    lookback: Option<u32>,
+    start: Option<String>,
+    end: Option<String>,
+    interval: Option<String>,
+    model: Option<String>,
+    lambda: Option<f64>,
+    horizon: Option<usize>,
+    source: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RollingVolatilityQuery {
+    token: Option<String>,
+    lookback: Option<u32>,
+    start: Option<String>,
+    end: Option<String>,
+    interval: Option<String>,
+    window: Option<usize>,
+    step: Option<usize>,
+    source: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CacheQuery {
+    symbol: Option<String>,
+    interval: Option<String>,
+    source: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct VolatilityQuery {
     token: Option<String>,
+    lookback: Option<u32>,
+    start: Option<String>,
+    end: Option<String>,
+    interval: Option<String>,
+    estimators: Option<String>,
+    estimation: Option<String>,
+    source: Option<String>,
 }
 
 #[get("/covariance")]
-pub async fn get_covariance(query: web::Query<CovarianceQuery>) -> impl Responder {
-    // Extract token_1 and token_2 strings from the query parameters
-    let token_1_str = match &query.token_1 {
-        Some(token) => token,
-        None => return HttpResponse::BadRequest().body("Missing query parameter: token_1"),
+pub async fn get_covariance(
+    data: web::Data<HistoricalData>,
+    query: web::Query<CovarianceQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let token_1 = parse_token(&query.token_1, "token_1")?;
+    let token_2 = parse_token(&query.token_2, "token_2")?;
+    let range = parse_range(query.lookback, &query.start, &query.end)?;
+    let interval = parse_interval(&query.interval, &range)?;
+    let confidence = match query.confidence {
+        Some(confidence) if confidence > 0.0 && confidence < 1.0 => confidence,
+        Some(confidence) => {
+            return Err(ApiError::invalid(
+                "confidence",
+                format!(
+                    "The confidence level must be between 0 and 1, got {}",
+                    confidence
+                ),
+            ))
+        }
+        None => DEFAULT_CONFIDENCE,
     };
 
-    let token_2_str = match &query.token_2 {
-        Some(token) => token,
-        None => return HttpResponse::BadRequest().body("Missing query parameter: token_2"),
+    let options = CorrelationOptions {
+        basis: parse_basis(&query.basis)?,
+        estimation: parse_estimation(&query.estimation)?,
+        method: parse_method(&query.method)?,
+        winsorize: parse_winsorize(query.winsorize)?,
+        half_life: parse_half_life(query.half_life)?,
+        confidence,
     };
+    let alignment = parse_alignment(&query.alignment)?;
+    let data = parse_source(&data, &query.source)?.with_alignment(alignment);
 
-    // Convert the token strings to the enum values
-    let token_1 = match Token::from_str(token_1_str) {
-        Some(token) => token,
-        None => {
-            return HttpResponse::BadRequest()
-                .body(format!("Invalid token_1 value: {}", token_1_str))
+    let result = data
+        .calculate_covariance(token_1, token_2, range, interval, options)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[get("/matrix")]
+pub async fn get_matrix(
+    data: web::Data<HistoricalData>,
+    query: web::Query<MatrixQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let symbols_str = required(&query.symbols, "symbols")?;
+
+    // Convert the comma-separated symbols to tokens, keeping their order
+    let mut tokens: Vec<Token> = Vec::new();
+    for symbol in symbols_str.split(',').filter(|s| !s.trim().is_empty()) {
+        let token = Token::from_str(symbol).ok_or_else(|| {
+            ApiError::invalid("symbols", format!("Invalid symbol value: {}", symbol))
+        })?;
+
+        if tokens.contains(&token) {
+            return Err(ApiError::invalid(
+                "symbols",
+                format!("Duplicate symbol value: {}", symbol),
+            ));
         }
+
+        tokens.push(token);
+    }
+
+    if tokens.len() < 2 || tokens.len() > MAX_MATRIX_TOKENS {
+        return Err(ApiError::invalid(
+            "symbols",
+            format!(
+                "Expected between 2 and {} symbols, got {}",
+                MAX_MATRIX_TOKENS,
+                tokens.len()
+            ),
+        ));
+    }
+
+    let range = parse_range(query.lookback, &query.start, &query.end)?;
+    let interval = parse_interval(&query.interval, &range)?;
+    let options = CorrelationOptions {
+        basis: parse_basis(&query.basis)?,
+        estimation: parse_estimation(&query.estimation)?,
+        method: parse_method(&query.method)?,
+        winsorize: parse_winsorize(query.winsorize)?,
+        half_life: parse_half_life(query.half_life)?,
+        ..CorrelationOptions::default()
     };
+    let alignment = parse_alignment(&query.alignment)?;
+    let data = parse_source(&data, &query.source)?.with_alignment(alignment);
+
+    let result = data
+        .calculate_matrix(tokens, range, interval, options)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[get("/volatility")]
+pub async fn get_volatility(
+    data: web::Data<HistoricalData>,
+    query: web::Query<VolatilityQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let token = parse_token(&query.token, "token")?;
+    let range = parse_range(query.lookback, &query.start, &query.end)?;
+    let interval = parse_interval(&query.interval, &range)?;
+    let estimators = parse_estimators(&query.estimators)?;
+    let estimation = parse_estimation(&query.estimation)?;
+    let data = parse_source(&data, &query.source)?;
+
+    let result = data
+        .calculate_realized_volatility(token, range, interval, &estimators, estimation)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
 
-    let token_2 = match Token::from_str(token_2_str) {
-        Some(token) => token,
-        None => {
-            return HttpResponse::BadRequest()
-                .body(format!("Invalid token_2 value: {}", token_2_str))
+#[get("/volatility/forecast")]
+pub async fn get_volatility_forecast(
+    data: web::Data<HistoricalData>,
+    query: web::Query<ForecastQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let token = parse_token(&query.token, "token")?;
+    let range = parse_range(query.lookback, &query.start, &query.end)?;
+    let interval = parse_interval(&query.interval, &range)?;
+
+    let model = match &query.model {
+        Some(model_str) => ForecastModel::from_str(model_str).ok_or_else(|| {
+            ApiError::invalid("model", format!("Invalid model value: {}", model_str))
+        })?,
+        None => ForecastModel::Garch,
+    };
+
+    let model = match (model, query.lambda) {
+        (ForecastModel::Ewma { .. }, Some(lambda)) if lambda > 0.0 && lambda < 1.0 => {
+            ForecastModel::Ewma { lambda }
+        }
+        (ForecastModel::Ewma { .. }, Some(lambda)) => {
+            return Err(ApiError::invalid(
+                "lambda",
+                format!("The decay factor must be between 0 and 1, got {}", lambda),
+            ))
+        }
+        (ForecastModel::Garch, Some(_)) => {
+            return Err(ApiError::invalid(
+                "lambda",
+                "The decay factor only applies to the ewma model",
+            ))
         }
+        (model, None) => model,
     };
 
-    match HistoricalData::calculate_covariance(token_1, token_2).await {
-        Ok(result) => HttpResponse::Ok().json(result),
-        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    let horizon = query.horizon.unwrap_or(DEFAULT_FORECAST_HORIZON);
+    if horizon == 0 || horizon > MAX_FORECAST_HORIZON {
+        return Err(ApiError::invalid(
+            "horizon",
+            format!(
+                "Expected a horizon between 1 and {} bars, got {}",
+                MAX_FORECAST_HORIZON, horizon
+            ),
+        ));
     }
+
+    let data = parse_source(&data, &query.source)?;
+
+    let result = data
+        .calculate_volatility_forecast(token, range, interval, model, horizon)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
 }
 
-#[get("/volatility")]
-pub async fn get_volatility(query: web::Query<VolatilityQuery>) -> impl Responder {
-    let token_str = match &query.token {
-        Some(token) => token,
-        None => return HttpResponse::BadRequest().body("Missing query parameter: token"),
-    };
+#[get("/beta")]
+pub async fn get_beta(
+    data: web::Data<HistoricalData>,
+    query: web::Query<BetaQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let token_1 = parse_token(&query.token_1, "token_1")?;
+    let token_2 = parse_token(&query.token_2, "token_2")?;
+    let range = parse_range(query.lookback, &query.start, &query.end)?;
+    let interval = parse_interval(&query.interval, &range)?;
+
+    let basis = parse_basis(&query.basis)?;
+    if basis == Basis::Price {
+        return Err(ApiError::invalid(
+            "basis",
+            "Beta is estimated on returns, expected simple_return or log_return",
+        ));
+    }
 
-    let token = match Token::from_str(token_str) {
-        Some(token) => token,
-        None => {
-            return HttpResponse::BadRequest().body(format!("Invalid token value: {}", token_str))
+    // The rolling beta is only computed when a window or step is given
+    let window = if query.window.is_some() || query.step.is_some() {
+        let window = parse_window(query.window, query.step)?;
+        if window.window < Regression::MIN_OBSERVATIONS {
+            return Err(ApiError::invalid(
+                "window",
+                format!(
+                    "A rolling beta needs a window of at least {} bars, got {}",
+                    Regression::MIN_OBSERVATIONS,
+                    window.window
+                ),
+            ));
         }
+        Some(window)
+    } else {
+        None
     };
+    let alignment = parse_alignment(&query.alignment)?;
+    let data = parse_source(&data, &query.source)?.with_alignment(alignment);
+
+    let result = data
+        .calculate_beta(token_1, token_2, range, interval, basis, window)
+        .await?;
 
-    match HistoricalData::calculate_realized_volatility(token).await {
-        Ok(result) => HttpResponse::Ok().json(result),
-        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[get("/crosscorrelation")]
+pub async fn get_cross_correlation(
+    data: web::Data<HistoricalData>,
+    query: web::Query<CrossCorrelationQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let token_1 = parse_token(&query.token_1, "token_1")?;
+    let token_2 = parse_token(&query.token_2, "token_2")?;
+    let range = parse_range(query.lookback, &query.start, &query.end)?;
+    let interval = parse_interval(&query.interval, &range)?;
+
+    let basis = parse_basis(&query.basis)?;
+    if basis == Basis::Price {
+        return Err(ApiError::invalid(
+            "basis",
+            "Cross-correlation is estimated on returns, expected simple_return or log_return",
+        ));
     }
+
+    let lags = parse_lags(query.max_lag, query.granger_lags)?;
+    let alignment = parse_alignment(&query.alignment)?;
+    let data = parse_source(&data, &query.source)?.with_alignment(alignment);
+
+    let result = data
+        .calculate_cross_correlation(token_1, token_2, range, interval, basis, lags)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[get("/rolling/correlation")]
+pub async fn get_rolling_correlation(
+    data: web::Data<HistoricalData>,
+    query: web::Query<RollingCorrelationQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let token_1 = parse_token(&query.token_1, "token_1")?;
+    let token_2 = parse_token(&query.token_2, "token_2")?;
+    let range = parse_range(query.lookback, &query.start, &query.end)?;
+    let interval = parse_interval(&query.interval, &range)?;
+    let basis = parse_basis(&query.basis)?;
+    let window = parse_window(query.window, query.step)?;
+    let alignment = parse_alignment(&query.alignment)?;
+    let data = parse_source(&data, &query.source)?.with_alignment(alignment);
+
+    let result = data
+        .calculate_rolling_correlation(token_1, token_2, range, interval, basis, window)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[get("/rolling/volatility")]
+pub async fn get_rolling_volatility(
+    data: web::Data<HistoricalData>,
+    query: web::Query<RollingVolatilityQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let token = parse_token(&query.token, "token")?;
+    let range = parse_range(query.lookback, &query.start, &query.end)?;
+    let interval = parse_interval(&query.interval, &range)?;
+    let window = parse_window(query.window, query.step)?;
+    let data = parse_source(&data, &query.source)?;
+
+    let result = data
+        .calculate_rolling_volatility(token, range, interval, window)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[get("/metrics")]
+pub async fn get_metrics(data: web::Data<HistoricalData>) -> impl Responder {
+    HttpResponse::Ok().json(json!({ "sources": data.metrics() }))
+}
+
+#[get("/cache")]
+pub async fn get_cache(data: web::Data<HistoricalData>) -> Result<HttpResponse, ApiError> {
+    let cache = data
+        .cache()
+        .ok_or_else(|| ApiError::NotFound("The price cache is disabled".to_string()))?;
+
+    let entries = PriceCache::blocking(cache, |cache| cache.entries()).await?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "path": cache.path(),
+        "ttl_secs": cache.ttl().as_secs(),
+        "entries": entries,
+    })))
+}
+
+#[delete("/cache")]
+pub async fn delete_cache(
+    data: web::Data<HistoricalData>,
+    query: web::Query<CacheQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let cache = data
+        .cache()
+        .ok_or_else(|| ApiError::NotFound("The price cache is disabled".to_string()))?;
+
+    // Resolve aliases so `btc` purges the `BTC-USD` entries
+    let symbol = match &query.symbol {
+        Some(symbol_str) => match Token::from_str(symbol_str) {
+            Some(token) => Some(token.id().to_string()),
+            None => {
+                return Err(ApiError::invalid(
+                    "symbol",
+                    format!("Invalid symbol value: {}", symbol_str),
+                ))
+            }
+        },
+        None => None,
+    };
+
+    let interval = match &query.interval {
+        Some(interval_str) => match Interval::from_str(interval_str) {
+            Some(interval) => Some(interval),
+            None => {
+                return Err(ApiError::invalid(
+                    "interval",
+                    format!("Invalid interval value: {}", interval_str),
+                ))
+            }
+        },
+        None => None,
+    };
+
+    let source = match &query.source {
+        Some(source_str) => match SourceKind::from_str(source_str) {
+            Some(source) => Some(source),
+            None => {
+                return Err(ApiError::invalid(
+                    "source",
+                    format!("Invalid source value: {}", source_str),
+                ))
+            }
+        },
+        None => None,
+    };
+
+    let purged = PriceCache::blocking(cache, move |cache| {
+        cache.purge(source, symbol.as_deref(), interval)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(json!({ "purged": purged })))
+}
+
+/// Returns a required query parameter.
+///
+/// # Arguments
+///
+/// * `value` - The value of the query parameter.
+/// * `name` - The name of the query parameter.
+///
+/// # Returns
+///
+/// * `Result<&str, ApiError>` - The value, or a validation error if it is missing.
+fn required<'a>(value: &'a Option<String>, name: &str) -> Result<&'a str, ApiError> {
+    value
+        .as_deref()
+        .ok_or_else(|| ApiError::invalid(name, format!("Missing query parameter: {}", name)))
+}
+
+/// Parses a required token query parameter.
+///
+/// # Arguments
+///
+/// * `value` - The value of the query parameter.
+/// * `name` - The name of the query parameter.
+///
+/// # Returns
+///
+/// * `Result<Token, ApiError>` - The token, or a validation error if it is missing or malformed.
+fn parse_token(value: &Option<String>, name: &str) -> Result<Token, ApiError> {
+    let token_str = required(value, name)?;
+
+    Token::from_str(token_str)
+        .ok_or_else(|| ApiError::invalid(name, format!("Invalid {} value: {}", name, token_str)))
+}
+
+/// Parses the `lookback`, `start` and `end` query parameters into a range.
+fn parse_range(
+    lookback: Option<u32>,
+    start: &Option<String>,
+    end: &Option<String>,
+) -> Result<DateRange, ApiError> {
+    DateRange::from_query(lookback, start.as_deref(), end.as_deref())
+        .map_err(|err| ApiError::validation(err.to_string()))
+}
+
+/// Parses the `interval` query parameter, defaulting to daily bars, and checks it against the range.
+fn parse_interval(value: &Option<String>, range: &DateRange) -> Result<Interval, ApiError> {
+    Interval::from_query(value.as_deref(), range)
+        .map_err(|err| ApiError::invalid("interval", err.to_string()))
+}
+
+/// Parses the `basis` query parameter, defaulting to `Basis::default()`.
+fn parse_basis(value: &Option<String>) -> Result<Basis, ApiError> {
+    match value {
+        Some(basis_str) => Basis::from_str(basis_str).ok_or_else(|| {
+            ApiError::invalid("basis", format!("Invalid basis value: {}", basis_str))
+        }),
+        None => Ok(Basis::default()),
+    }
+}
+
+/// Parses the `estimation` query parameter, defaulting to `Estimation::default()`.
+fn parse_estimation(value: &Option<String>) -> Result<Estimation, ApiError> {
+    match value {
+        Some(estimation_str) => Estimation::from_str(estimation_str).ok_or_else(|| {
+            ApiError::invalid(
+                "estimation",
+                format!("Invalid estimation value: {}", estimation_str),
+            )
+        }),
+        None => Ok(Estimation::default()),
+    }
+}
+
+/// Parses the `alignment` query parameter, defaulting to `Alignment::default()`.
+fn parse_alignment(value: &Option<String>) -> Result<Alignment, ApiError> {
+    match value {
+        Some(alignment_str) => Alignment::from_str(alignment_str).ok_or_else(|| {
+            ApiError::invalid(
+                "alignment",
+                format!("Invalid alignment value: {}", alignment_str),
+            )
+        }),
+        None => Ok(Alignment::default()),
+    }
+}
+
+/// Parses the `method` query parameter, defaulting to `CorrelationMethod::default()`.
+fn parse_method(value: &Option<String>) -> Result<CorrelationMethod, ApiError> {
+    match value {
+        Some(method_str) => CorrelationMethod::from_str(method_str).ok_or_else(|| {
+            ApiError::invalid("method", format!("Invalid method value: {}", method_str))
+        }),
+        None => Ok(CorrelationMethod::default()),
+    }
+}
+
+/// Validates the `winsorize` query parameter, the fraction of values clipped in each tail.
+fn parse_winsorize(winsorize: Option<f64>) -> Result<Option<f64>, ApiError> {
+    match winsorize {
+        Some(fraction) if !(fraction > 0.0 && fraction < 0.5) => Err(ApiError::invalid(
+            "winsorize",
+            format!(
+                "The winsorizing fraction must be between 0 and 0.5, got {}",
+                fraction
+            ),
+        )),
+        winsorize => Ok(winsorize),
+    }
+}
+
+/// Validates the `half_life` query parameter, in bars.
+fn parse_half_life(half_life: Option<f64>) -> Result<Option<f64>, ApiError> {
+    match half_life {
+        Some(half_life) if !(half_life.is_finite() && half_life > 0.0) => Err(ApiError::invalid(
+            "half_life",
+            format!(
+                "The half-life must be a positive number of bars, got {}",
+                half_life
+            ),
+        )),
+        half_life => Ok(half_life),
+    }
+}
+
+/// Parses the comma-separated `estimators` query parameter, none by default.
+fn parse_estimators(value: &Option<String>) -> Result<Vec<Estimator>, ApiError> {
+    match value {
+        Some(estimators_str) => Estimator::from_list(estimators_str).ok_or_else(|| {
+            ApiError::invalid(
+                "estimators",
+                format!("Invalid estimators value: {}", estimators_str),
+            )
+        }),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Parses the `window` and `step` query parameters of a rolling statistic.
+fn parse_window(window: Option<usize>, step: Option<usize>) -> Result<RollingWindow, ApiError> {
+    let window = RollingWindow {
+        window: window.unwrap_or(DEFAULT_ROLLING_WINDOW),
+        step: step.unwrap_or(1),
+    };
+
+    if window.window < 2 || window.step == 0 {
+        return Err(ApiError::validation(
+            "The rolling window must be at least 2 and the step at least 1",
+        ));
+    }
+
+    Ok(window)
+}
+
+/// Validates the `max_lag` and `granger_lags` query parameters, in bars.
+fn parse_lags(
+    max_lag: Option<usize>,
+    granger_lags: Option<usize>,
+) -> Result<CrossCorrelationLags, ApiError> {
+    let max_lag = max_lag.unwrap_or(DEFAULT_MAX_LAG);
+    if max_lag > MAX_LAG {
+        return Err(ApiError::invalid(
+            "max_lag",
+            format!(
+                "Expected a lag of at most {} bars, got {}",
+                MAX_LAG, max_lag
+            ),
+        ));
+    }
+
+    // The causality models default to the lags of the cross-correlation
+    let granger_lags = granger_lags.unwrap_or(max_lag.max(1));
+    if granger_lags == 0 || granger_lags > MAX_LAG {
+        return Err(ApiError::invalid(
+            "granger_lags",
+            format!(
+                "Expected between 1 and {} Granger lags, got {}",
+                MAX_LAG, granger_lags
+            ),
+        ));
+    }
+
+    Ok(CrossCorrelationLags {
+        max_lag,
+        granger_lags,
+    })
+}
+
+/// Selects the price source named by the `source` query parameter.
+fn parse_source(data: &HistoricalData, value: &Option<String>) -> Result<HistoricalData, ApiError> {
+    data.with_source(value.as_deref())
+        .map_err(|err| ApiError::invalid("source", err.to_string()))
 }
@@ -0,0 +1,152 @@
+use super::{PriceSource, SourceKind};
+use crate::error::{UnknownSymbol, Unsupported};
+use crate::interval::Interval;
+use crate::range::DateRange;
+use crate::request::{Request, RequestError};
+use crate::series::{Bar, PriceSeries};
+use crate::token::Token;
+use async_trait::async_trait;
+use chrono::DateTime;
+use reqwest::{Method, StatusCode};
+use serde_json::Value;
+
+/// Error code of the Binance API for an unknown symbol.
+const INVALID_SYMBOL_CODE: &str = "-1121";
+
+/// Maximum number of klines Binance returns per request.
+const KLINES_LIMIT: usize = 1000;
+
+/// Price source backed by the Binance spot klines API (crypto only).
+pub struct BinanceSource {
+    request: Request,
+}
+
+#[async_trait]
+impl PriceSource for BinanceSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::Binance
+    }
+
+    async fn fetch(
+        &self,
+        token: &Token,
+        range: &DateRange,
+        interval: Interval,
+    ) -> Result<PriceSeries, anyhow::Error> {
+        let (base, quote) = token.crypto_pair().ok_or_else(|| {
+            Unsupported(format!(
+                "Binance only serves crypto pairs, got token<{}>.",
+                token.id()
+            ))
+        })?;
+
+        // Binance has no USD spot books, USDT is the closest quote
+        let quote = if quote == "USD" { "USDT" } else { quote };
+        let symbol = format!("{}{}", base, quote);
+
+        let mut raw_bars: Vec<Option<Bar>> = Vec::new();
+        let mut start_time = range.start.timestamp_millis();
+        let end_time = range.end.timestamp_millis();
+
+        // Page through the range, KLINES_LIMIT bars at a time
+        loop {
+            let klines: Vec<Value> = self
+                .request
+                .call(Method::GET, "https://api.binance.com/api/v3/klines")
+                .query("symbol", &symbol)
+                .query("interval", Self::interval_id(interval))
+                .query("startTime", start_time)
+                .query("endTime", end_time)
+                .query("limit", KLINES_LIMIT)
+                .json()
+                .await
+                .map_err(|err| Self::classify(err, token))?;
+
+            for kline in &klines {
+                raw_bars.push(Self::parse_kline(kline, interval));
+            }
+
+            let last_open_time = klines.last().and_then(|kline| kline[0].as_i64());
+
+            match last_open_time {
+                Some(open_time) if klines.len() == KLINES_LIMIT && open_time < end_time => {
+                    start_time = open_time + 1;
+                }
+                _ => break,
+            }
+        }
+
+        Ok(PriceSeries::from_raw_bars(token, interval, raw_bars))
+    }
+}
+
+impl BinanceSource {
+    /// Creates the source.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The requests to Binance.
+    ///
+    /// # Returns
+    ///
+    /// * `BinanceSource` - The source.
+    pub fn new(request: Request) -> BinanceSource {
+        BinanceSource { request }
+    }
+
+    /// Reports the error Binance returns for an unknown pair as an unknown symbol.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - The error of a klines request.
+    /// * `token` - The token being fetched.
+    ///
+    /// # Returns
+    ///
+    /// * `anyhow::Error` - The classified error.
+    fn classify(err: RequestError, token: &Token) -> anyhow::Error {
+        match &err {
+            RequestError::Status { status, body, .. }
+                if *status == StatusCode::BAD_REQUEST && body.contains(INVALID_SYMBOL_CODE) =>
+            {
+                UnknownSymbol(format!("Binance has no pair for token<{}>.", token.id())).into()
+            }
+            _ => err.into(),
+        }
+    }
+
+    /// Returns the identifier used in Binance API for the interval.
+    fn interval_id(interval: Interval) -> &'static str {
+        match interval {
+            Interval::Hourly => "1h",
+            Interval::Daily => "1d",
+            Interval::Weekly => "1w",
+            Interval::Monthly => "1M",
+        }
+    }
+
+    /// Parses a kline array `[open time, open, high, low, close, volume, ...]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `kline` - The kline array, with prices encoded as strings.
+    /// * `interval` - The bar interval, used to compute the bar start.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Bar>` - The bar, `None` if the kline is malformed.
+    fn parse_kline(kline: &Value, interval: Interval) -> Option<Bar> {
+        let price = |i: usize| kline[i].as_str()?.parse::<f64>().ok();
+        let open_time = DateTime::from_timestamp_millis(kline[0].as_i64()?)?;
+
+        Some(Bar {
+            date: interval.bar_start(open_time),
+            open: price(1)?,
+            high: price(2)?,
+            low: price(3)?,
+            close: price(4)?,
+            volume: price(5),
+            adjclose: None,
+        })
+    }
+}
@@ -0,0 +1,191 @@
+use super::{PriceSource, SourceKind};
+use crate::interval::Interval;
+use crate::metrics::ProviderMetrics;
+use crate::range::DateRange;
+use crate::series::PriceSeries;
+use crate::token::Token;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use futures::future::{BoxFuture, FutureExt, Shared};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// A fetch shared by every caller that joined it.
+type SharedFetch = Shared<BoxFuture<'static, Result<PriceSeries, SharedError>>>;
+
+/// Fetches in flight, by symbol and interval.
+type InFlight = HashMap<(String, Interval), Vec<Flight>>;
+
+/// A fetch in flight and the range it covers.
+struct Flight {
+    id: u64,
+    range: DateRange,
+    fetch: SharedFetch,
+}
+
+/// Error of a shared fetch, handed to every caller that joined it.
+///
+/// The original error stays reachable through `source()`, so callers can still
+/// find typed errors in the error chain.
+#[derive(Debug, Clone)]
+pub struct SharedError(Arc<anyhow::Error>);
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for SharedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&**self.0)
+    }
+}
+
+/// Wraps a price source so concurrent fetches of the same bars share one upstream fetch.
+///
+/// A fetch joins one in flight for the same symbol and interval when the
+/// in-flight range covers its own; the shared bars are then trimmed to the
+/// requested range.
+pub struct CoalescingSource {
+    inner: Arc<dyn PriceSource>,
+    in_flight: Arc<Mutex<InFlight>>,
+    next_id: Mutex<u64>,
+    metrics: Arc<ProviderMetrics>,
+}
+
+#[async_trait]
+impl PriceSource for CoalescingSource {
+    fn kind(&self) -> SourceKind {
+        self.inner.kind()
+    }
+
+    async fn fetch(
+        &self,
+        token: &Token,
+        range: &DateRange,
+        interval: Interval,
+    ) -> Result<PriceSeries, anyhow::Error> {
+        let key = (token.id().to_string(), interval);
+        let (fetch, covered) = self.join_or_start(key, token, range, interval);
+
+        let series = fetch.await?;
+
+        if covered == *range {
+            return Ok(series);
+        }
+
+        Ok(series.within(token, interval, range))
+    }
+
+    async fn prepare(&self) -> Result<(), anyhow::Error> {
+        self.inner.prepare().await
+    }
+}
+
+impl CoalescingSource {
+    /// Wraps a price source.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The wrapped source.
+    /// * `metrics` - The metrics of the provider, recording fetches and coalesced ones.
+    ///
+    /// # Returns
+    ///
+    /// * `CoalescingSource` - The wrapped source.
+    pub fn new(inner: Arc<dyn PriceSource>, metrics: Arc<ProviderMetrics>) -> CoalescingSource {
+        CoalescingSource {
+            inner,
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+            next_id: Mutex::new(0),
+            metrics,
+        }
+    }
+
+    /// Joins a fetch in flight covering the range, or starts a new one.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The symbol and interval of the fetch.
+    /// * `token` - The token to fetch.
+    /// * `range` - The range to fetch.
+    /// * `interval` - The bar interval to fetch.
+    ///
+    /// # Returns
+    ///
+    /// * `(SharedFetch, DateRange)` - The shared fetch and the range it covers.
+    fn join_or_start(
+        &self,
+        key: (String, Interval),
+        token: &Token,
+        range: &DateRange,
+        interval: Interval,
+    ) -> (SharedFetch, DateRange) {
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|err| err.into_inner());
+
+        let joined = in_flight.get(&key).and_then(|flights| {
+            flights
+                .iter()
+                .find(|flight| flight.range.start <= range.start && flight.range.end >= range.end)
+        });
+
+        if let Some(flight) = joined {
+            debug!(
+                "Joining the {} fetch of token<{}> in flight.",
+                self.kind().id(),
+                token.id()
+            );
+            self.metrics.record_coalesced();
+            return (flight.fetch.clone(), flight.range);
+        }
+
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap_or_else(|err| err.into_inner());
+            *next_id += 1;
+            *next_id
+        };
+
+        let inner = self.inner.clone();
+        let registry = self.in_flight.clone();
+        let (token, range, flight_key) = (token.clone(), *range, key.clone());
+
+        // The fetch runs on its own task, so it completes and leaves the registry
+        // even when every caller that joined it has been dropped
+        let task = tokio::spawn(async move {
+            let result = inner.fetch(&token, &range, interval).await;
+
+            // Later callers start a new fetch once this one has completed
+            let mut in_flight = registry.lock().unwrap_or_else(|err| err.into_inner());
+            if let Some(flights) = in_flight.get_mut(&flight_key) {
+                flights.retain(|flight| flight.id != id);
+                if flights.is_empty() {
+                    in_flight.remove(&flight_key);
+                }
+            }
+
+            result
+        });
+
+        let fetch = async move {
+            match task.await {
+                Ok(result) => result,
+                Err(err) => Err(anyhow!("The fetch task failed: {}", err)),
+            }
+            .map_err(|err| SharedError(Arc::new(err)))
+        }
+        .boxed()
+        .shared();
+
+        self.metrics.record_fetch();
+        in_flight.entry(key).or_default().push(Flight {
+            id,
+            range,
+            fetch: fetch.clone(),
+        });
+
+        (fetch, range)
+    }
+}
@@ -0,0 +1,151 @@
+use super::{PriceSource, SourceKind};
+use crate::error::Unsupported;
+use crate::interval::Interval;
+use crate::range::DateRange;
+use crate::request::Request;
+use crate::series::PriceSeries;
+use crate::token::Token;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveTime};
+use reqwest::{header::HeaderMap, Method};
+use serde::Deserialize;
+
+/// Longest range for which CoinGecko serves hourly prices.
+const MAX_HOURLY_RANGE_DAYS: i64 = 90;
+
+/// CoinGecko coin ids of common tickers; other tickers are tried as ids.
+const COIN_IDS: &[(&str, &str)] = &[
+    ("BTC", "bitcoin"),
+    ("ETH", "ethereum"),
+    ("SOL", "solana"),
+    ("BNB", "binancecoin"),
+    ("XRP", "ripple"),
+    ("ADA", "cardano"),
+    ("DOGE", "dogecoin"),
+    ("DOT", "polkadot"),
+    ("AVAX", "avalanche-2"),
+    ("LINK", "chainlink"),
+    ("LTC", "litecoin"),
+];
+
+/// Price source backed by the CoinGecko market chart API (crypto only).
+///
+/// Prices are dated by the day they close on, like Binance daily klines, so
+/// switching crypto sources does not shift their returns against equities.
+pub struct CoinGeckoSource {
+    request: Request,
+    api_key: Option<String>,
+}
+
+/// Response of the `market_chart/range` endpoint.
+#[derive(Debug, Deserialize)]
+struct MarketChart {
+    /// `[timestamp in milliseconds, price]` pairs.
+    prices: Vec<(f64, Option<f64>)>,
+}
+
+#[async_trait]
+impl PriceSource for CoinGeckoSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::CoinGecko
+    }
+
+    async fn fetch(
+        &self,
+        token: &Token,
+        range: &DateRange,
+        interval: Interval,
+    ) -> Result<PriceSeries, anyhow::Error> {
+        let (base, quote) = token.crypto_pair().ok_or_else(|| {
+            Unsupported(format!(
+                "CoinGecko only serves crypto pairs, got token<{}>.",
+                token.id()
+            ))
+        })?;
+
+        // CoinGecko picks the granularity from the range length
+        if interval == Interval::Hourly
+            && range.end - range.start > Duration::days(MAX_HOURLY_RANGE_DAYS)
+        {
+            return Err(Unsupported(format!(
+                "CoinGecko only serves hourly prices for ranges up to {} days.",
+                MAX_HOURLY_RANGE_DAYS
+            ))
+            .into());
+        }
+
+        let url = format!(
+            "https://api.coingecko.com/api/v3/coins/{}/market_chart/range",
+            urlencoding::encode(&Self::coin_id(base))
+        );
+
+        let chart: MarketChart = self
+            .request
+            .call(Method::GET, url)
+            .headers(self.build_headers())
+            .query("vs_currency", quote.to_lowercase())
+            .query("from", range.start.timestamp())
+            .query("to", range.end.timestamp())
+            .json()
+            .await?;
+
+        // Longer ranges get daily points stamped at midnight UTC, each being the
+        // close of the day before. They are moved just before midnight, so the day
+        // they close dates them as it dates Binance klines; the live last point stays.
+        let daily_points = range.end - range.start > Duration::days(MAX_HOURLY_RANGE_DAYS);
+
+        let points = chart
+            .prices
+            .into_iter()
+            .filter_map(|(timestamp, price)| {
+                let date = DateTime::from_timestamp_millis(timestamp as i64)?;
+                let date = if daily_points && date.time() == NaiveTime::MIN {
+                    date - Duration::seconds(1)
+                } else {
+                    date
+                };
+
+                Some((date, price))
+            })
+            .collect();
+
+        Ok(PriceSeries::from_points(token, interval, points))
+    }
+}
+
+impl CoinGeckoSource {
+    /// Creates the source.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The requests to CoinGecko.
+    /// * `api_key` - Optional CoinGecko demo API key.
+    ///
+    /// # Returns
+    ///
+    /// * `CoinGeckoSource` - The source.
+    pub fn new(request: Request, api_key: Option<String>) -> CoinGeckoSource {
+        CoinGeckoSource { request, api_key }
+    }
+
+    /// Returns the CoinGecko coin id of a base currency.
+    fn coin_id(base: &str) -> String {
+        COIN_IDS
+            .iter()
+            .find(|(ticker, _)| *ticker == base)
+            .map(|(_, id)| id.to_string())
+            .unwrap_or_else(|| base.to_lowercase())
+    }
+
+    /// Builds the headers for the request to CoinGecko, including the API key if configured.
+    fn build_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("accept", "application/json".parse().unwrap());
+
+        if let Some(api_key) = self.api_key.as_ref().and_then(|key| key.parse().ok()) {
+            headers.insert("x-cg-demo-api-key", api_key);
+        }
+
+        headers
+    }
+}
@@ -0,0 +1,641 @@
+use super::{PriceSource, SourceKind};
+use crate::error::UnknownSymbol;
+use crate::interval::Interval;
+use crate::range::DateRange;
+use crate::series::{Bar, PriceSeries};
+use crate::token::Token;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use parquet::file::reader::SerializedFileReader;
+use parquet::record::Field;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Formats of the naive dates accepted in date columns, besides RFC 3339 and unix timestamps.
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"];
+
+/// Unix timestamps above this value are taken as milliseconds.
+const MILLISECONDS_THRESHOLD: i64 = 100_000_000_000;
+
+/// Enum representing the supported file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Csv,
+    Parquet,
+}
+
+impl FileFormat {
+    /// Returns the file extension of the format.
+    pub fn extension(&self) -> &str {
+        match *self {
+            FileFormat::Csv => "csv",
+            FileFormat::Parquet => "parquet",
+        }
+    }
+
+    /// Creates a `FileFormat` enum from a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - A string slice representing the format name.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<FileFormat>` - Representing the format if valid, `None` if invalid.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(format: &str) -> Option<FileFormat> {
+        match format.to_lowercase().as_str() {
+            "csv" => Some(FileFormat::Csv),
+            "parquet" | "pq" => Some(FileFormat::Parquet),
+            _ => None,
+        }
+    }
+}
+
+/// Names of the columns holding each bar field.
+///
+/// Column names are matched case-insensitively, ignoring anything but letters
+/// and digits, so the defaults also match headers like `Date` or `Adj Close`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMapping {
+    pub date: String,
+    pub open: String,
+    pub high: String,
+    pub low: String,
+    pub close: String,
+    pub volume: String,
+    pub adjclose: String,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        ColumnMapping {
+            date: "date".to_string(),
+            open: "open".to_string(),
+            high: "high".to_string(),
+            low: "low".to_string(),
+            close: "close".to_string(),
+            volume: "volume".to_string(),
+            adjclose: "adjclose".to_string(),
+        }
+    }
+}
+
+impl ColumnMapping {
+    /// Creates a column mapping from `field=column` pairs, e.g. `date=Timestamp,close=Last`.
+    ///
+    /// Fields that are not listed keep their default column name.
+    ///
+    /// # Arguments
+    ///
+    /// * `mapping` - A string slice holding comma-separated `field=column` pairs.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<ColumnMapping>` - Representing the mapping if valid, `None` if a pair is malformed or names an unknown field.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(mapping: &str) -> Option<ColumnMapping> {
+        let mut columns = ColumnMapping::default();
+
+        for pair in mapping.split(',').filter(|pair| !pair.trim().is_empty()) {
+            let (field, column) = pair.split_once('=')?;
+            let column = column.trim().to_string();
+
+            match field.trim().to_lowercase().as_str() {
+                "date" => columns.date = column,
+                "open" => columns.open = column,
+                "high" => columns.high = column,
+                "low" => columns.low = column,
+                "close" => columns.close = column,
+                "volume" => columns.volume = column,
+                "adjclose" => columns.adjclose = column,
+                _ => return None,
+            }
+        }
+
+        Some(columns)
+    }
+
+    /// Resolves the mapped columns against the header of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The column names of the file, in order.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ColumnIndices, anyhow::Error>` - The column positions or an error if the date or close column is missing.
+    fn resolve(&self, header: &[String]) -> Result<ColumnIndices, anyhow::Error> {
+        let position = |column: &str| {
+            let column = Self::normalize(column);
+            header
+                .iter()
+                .position(|name| Self::normalize(name) == column)
+        };
+
+        let required = |column: &str| {
+            position(column).ok_or_else(|| {
+                anyhow!(
+                    "Column <{}> not found, available columns: {}",
+                    column,
+                    header.join(", ")
+                )
+            })
+        };
+
+        Ok(ColumnIndices {
+            date: required(&self.date)?,
+            close: required(&self.close)?,
+            open: position(&self.open),
+            high: position(&self.high),
+            low: position(&self.low),
+            volume: position(&self.volume),
+            adjclose: position(&self.adjclose),
+        })
+    }
+
+    /// Lowercases a column name and strips anything but letters and digits.
+    fn normalize(column: &str) -> String {
+        column
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    }
+}
+
+/// Positions of the mapped columns in a file, optional ones `None` when absent.
+struct ColumnIndices {
+    date: usize,
+    close: usize,
+    open: Option<usize>,
+    high: Option<usize>,
+    low: Option<usize>,
+    volume: Option<usize>,
+    adjclose: Option<usize>,
+}
+
+/// A single value read from a file, before it is interpreted as a bar field.
+enum Cell {
+    Null,
+    Number(f64),
+    Text(String),
+    Timestamp(DateTime<Utc>),
+    Date(NaiveDate),
+}
+
+/// Settings of the file-backed price source.
+#[derive(Debug, Clone)]
+pub struct FileSourceConfig {
+    /// Directory holding one `<SYMBOL>.csv` or `<SYMBOL>.parquet` file per symbol.
+    pub dir: PathBuf,
+    /// Only read files of this format, both formats if `None`.
+    pub format: Option<FileFormat>,
+    /// Names of the columns holding each bar field.
+    pub columns: ColumnMapping,
+    /// Timezone of naive dates in the files; dates with an offset keep their own.
+    pub timezone: Tz,
+}
+
+impl Default for FileSourceConfig {
+    fn default() -> Self {
+        FileSourceConfig {
+            dir: PathBuf::from("data"),
+            format: None,
+            columns: ColumnMapping::default(),
+            timezone: Tz::UTC,
+        }
+    }
+}
+
+/// Price source reading OHLCV bars from local CSV and Parquet files.
+///
+/// Files may hold bars of any interval up to the requested one, finer bars
+/// are merged into the requested interval.
+pub struct FileSource {
+    config: FileSourceConfig,
+}
+
+#[async_trait]
+impl PriceSource for FileSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::File
+    }
+
+    async fn fetch(
+        &self,
+        token: &Token,
+        range: &DateRange,
+        interval: Interval,
+    ) -> Result<PriceSeries, anyhow::Error> {
+        let (path, format) = self.find_file(token)?;
+        let config = self.config.clone();
+        let range = *range;
+        let first_bar = interval.bar_start(range.start);
+
+        debug!("Reading token<{}> bars from {:?}.", token.id(), path);
+
+        // File reads and parsing are blocking, keep them off the async workers
+        let raw_bars = tokio::task::spawn_blocking(move || {
+            let rows = match format {
+                FileFormat::Csv => Self::read_csv(&path, &config.columns),
+                FileFormat::Parquet => Self::read_parquet(&path, &config.columns),
+            }
+            .map_err(|err| anyhow!("Failed to read {:?}: {}", path, err))?;
+
+            let raw_bars: Vec<Option<Bar>> = rows
+                .into_iter()
+                .map(|row| Self::parse_bar(row, config.timezone))
+                .filter(|bar| match bar {
+                    // The bar the range starts in is kept, as other sources return it too
+                    Some(bar) => {
+                        let date = bar.date.and_utc();
+                        interval.local_bar_start(date, config.timezone) >= first_bar
+                            && date <= range.end
+                    }
+                    None => true,
+                })
+                .collect();
+
+            Ok::<Vec<Option<Bar>>, anyhow::Error>(raw_bars)
+        })
+        .await??;
+
+        Ok(PriceSeries::from_finer_bars(
+            token,
+            interval,
+            raw_bars,
+            self.config.timezone,
+        ))
+    }
+}
+
+impl FileSource {
+    /// Creates the source.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The directory, format, column mapping and timezone of the files.
+    ///
+    /// # Returns
+    ///
+    /// * `FileSource` - The source.
+    pub fn new(config: FileSourceConfig) -> FileSource {
+        FileSource { config }
+    }
+
+    /// Finds the file of a token, trying the symbol as is and lowercased.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token to find the file of.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(PathBuf, FileFormat), anyhow::Error>` - The file and its format, or an error if there is none.
+    fn find_file(&self, token: &Token) -> Result<(PathBuf, FileFormat), anyhow::Error> {
+        let formats = match self.config.format {
+            Some(format) => vec![format],
+            None => vec![FileFormat::Csv, FileFormat::Parquet],
+        };
+
+        let names = [token.id().to_string(), token.id().to_lowercase()];
+
+        names
+            .iter()
+            .flat_map(|name| {
+                formats.iter().map(move |format| {
+                    let path = self
+                        .config
+                        .dir
+                        .join(format!("{}.{}", name, format.extension()));
+                    (path, *format)
+                })
+            })
+            .find(|(path, _)| path.is_file())
+            .ok_or_else(|| {
+                UnknownSymbol(format!(
+                    "No file found for token<{}> in {:?}.",
+                    token.id(),
+                    self.config.dir
+                ))
+                .into()
+            })
+    }
+
+    /// Reads the mapped columns of a CSV file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The CSV file, with a header row.
+    /// * `columns` - The column mapping.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<[Cell; 7]>, anyhow::Error>` - The date, open, high, low, close, volume and adjusted close of each row, or an error.
+    fn read_csv(path: &Path, columns: &ColumnMapping) -> Result<Vec<[Cell; 7]>, anyhow::Error> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_path(path)?;
+        let header: Vec<String> = reader.headers()?.iter().map(str::to_string).collect();
+        let indices = columns.resolve(&header)?;
+
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            let record = record?;
+            let cell = |index: Option<usize>| match index.and_then(|i| record.get(i)) {
+                Some(value) if !value.is_empty() => Ok(Cell::Text(value.to_string())),
+                _ => Ok(Cell::Null),
+            };
+
+            rows.push(Self::select(&indices, cell)?);
+        }
+
+        Ok(rows)
+    }
+
+    /// Reads the mapped columns of a Parquet file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The Parquet file.
+    /// * `columns` - The column mapping.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<[Cell; 7]>, anyhow::Error>` - The date, open, high, low, close, volume and adjusted close of each row, or an error.
+    fn read_parquet(path: &Path, columns: &ColumnMapping) -> Result<Vec<[Cell; 7]>, anyhow::Error> {
+        let reader = SerializedFileReader::new(File::open(path)?)?;
+        let mut indices: Option<ColumnIndices> = None;
+
+        let mut rows = Vec::new();
+        for row in reader {
+            let fields = row?.into_columns();
+
+            if indices.is_none() {
+                let header: Vec<String> = fields.iter().map(|(name, _)| name.clone()).collect();
+                indices = Some(columns.resolve(&header)?);
+            }
+
+            let cell = |index: Option<usize>| match index.map(|i| &fields[i].1) {
+                Some(field) => Self::parquet_cell(field),
+                None => Ok(Cell::Null),
+            };
+
+            rows.push(Self::select(indices.as_ref().unwrap(), cell)?);
+        }
+
+        Ok(rows)
+    }
+
+    /// Picks the mapped cells of a row, in bar field order.
+    fn select<F>(indices: &ColumnIndices, cell: F) -> Result<[Cell; 7], anyhow::Error>
+    where
+        F: Fn(Option<usize>) -> Result<Cell, anyhow::Error>,
+    {
+        Ok([
+            cell(Some(indices.date))?,
+            cell(indices.open)?,
+            cell(indices.high)?,
+            cell(indices.low)?,
+            cell(Some(indices.close))?,
+            cell(indices.volume)?,
+            cell(indices.adjclose)?,
+        ])
+    }
+
+    /// Converts a Parquet field into a cell, failing on dates outside the calendar.
+    fn parquet_cell(field: &Field) -> Result<Cell, anyhow::Error> {
+        let cell = match field {
+            Field::Byte(value) => Cell::Number(*value as f64),
+            Field::Short(value) => Cell::Number(*value as f64),
+            Field::Int(value) => Cell::Number(*value as f64),
+            Field::Long(value) => Cell::Number(*value as f64),
+            Field::UByte(value) => Cell::Number(*value as f64),
+            Field::UShort(value) => Cell::Number(*value as f64),
+            Field::UInt(value) => Cell::Number(*value as f64),
+            Field::ULong(value) => Cell::Number(*value as f64),
+            Field::Float(value) => Cell::Number(*value as f64),
+            Field::Double(value) => Cell::Number(*value),
+            Field::Str(value) => Cell::Text(value.clone()),
+            Field::Date(days) => {
+                // Parquet dates count days from the Unix epoch, day 719 163 of the common era
+                let date = days
+                    .checked_add(719_163)
+                    .and_then(NaiveDate::from_num_days_from_ce_opt)
+                    .ok_or_else(|| {
+                        anyhow!("The date <{}> days after 1970-01-01 is out of range.", days)
+                    })?;
+                Cell::Date(date)
+            }
+            Field::TimestampMillis(millis) => DateTime::from_timestamp_millis(*millis)
+                .map(Cell::Timestamp)
+                .unwrap_or(Cell::Null),
+            Field::TimestampMicros(micros) => DateTime::from_timestamp_micros(*micros)
+                .map(Cell::Timestamp)
+                .unwrap_or(Cell::Null),
+            _ => Cell::Null,
+        };
+
+        Ok(cell)
+    }
+
+    /// Interprets the cells of a row as a bar.
+    ///
+    /// Missing open, high and low prices fall back to the close.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The date, open, high, low, close, volume and adjusted close cells.
+    /// * `timezone` - The timezone of naive dates.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Bar>` - The bar dated in UTC, `None` if the date or close is missing or malformed.
+    fn parse_bar(row: [Cell; 7], timezone: Tz) -> Option<Bar> {
+        let [date, open, high, low, close, volume, adjclose] = row;
+
+        let date = Self::parse_date(date, timezone)?;
+        let close = Self::parse_number(close)?;
+
+        Some(Bar {
+            date: date.naive_utc(),
+            open: Self::parse_number(open).unwrap_or(close),
+            high: Self::parse_number(high).unwrap_or(close),
+            low: Self::parse_number(low).unwrap_or(close),
+            close,
+            volume: Self::parse_number(volume),
+            adjclose: Self::parse_number(adjclose),
+        })
+    }
+
+    /// Interprets a cell as a price or volume.
+    fn parse_number(cell: Cell) -> Option<f64> {
+        match cell {
+            Cell::Number(value) => Some(value),
+            Cell::Text(value) => value.parse().ok(),
+            _ => None,
+        }
+        .filter(|value: &f64| value.is_finite())
+    }
+
+    /// Interprets a cell as a date.
+    ///
+    /// Accepts RFC 3339 dates, unix timestamps in seconds or milliseconds and
+    /// naive dates, which are taken in the configured timezone. Ambiguous local
+    /// times resolve to the earliest instant, nonexistent ones are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - The date cell.
+    /// * `timezone` - The timezone of naive dates.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<DateTime<Utc>>` - The date, `None` if it is missing or malformed.
+    fn parse_date(cell: Cell, timezone: Tz) -> Option<DateTime<Utc>> {
+        let naive = match cell {
+            Cell::Timestamp(date) => return Some(date),
+            Cell::Number(timestamp) => return Self::from_unix(timestamp as i64),
+            Cell::Date(date) => date.and_hms_opt(0, 0, 0)?,
+            Cell::Text(value) => {
+                if let Ok(date) = DateTime::parse_from_rfc3339(&value) {
+                    return Some(date.with_timezone(&Utc));
+                }
+
+                if let Ok(timestamp) = value.parse::<i64>() {
+                    return Self::from_unix(timestamp);
+                }
+
+                match NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+                    Ok(date) => date.and_hms_opt(0, 0, 0)?,
+                    Err(_) => DATE_FORMATS
+                        .iter()
+                        .find_map(|format| NaiveDateTime::parse_from_str(&value, format).ok())?,
+                }
+            }
+            Cell::Null => return None,
+        };
+
+        timezone
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|date| date.with_timezone(&Utc))
+    }
+
+    /// Converts a unix timestamp in seconds or milliseconds into a date.
+    fn from_unix(timestamp: i64) -> Option<DateTime<Utc>> {
+        if timestamp.abs() >= MILLISECONDS_THRESHOLD {
+            DateTime::from_timestamp_millis(timestamp)
+        } else {
+            DateTime::from_timestamp(timestamp, 0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileSource, FileSourceConfig};
+    use crate::interval::Interval;
+    use crate::range::DateRange;
+    use crate::source::PriceSource;
+    use crate::token::Token;
+    use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
+    use chrono_tz::Tz;
+    use std::fs;
+
+    /// Writes a CSV fixture of naive daily dates into its own directory.
+    fn fixture(name: &str, rows: &[(&str, f64)]) -> FileSourceConfig {
+        let dir = std::env::temp_dir().join(format!("file-source-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut csv = String::from("date,close\n");
+        for (date, close) in rows {
+            csv.push_str(&format!("{},{}\n", date, close));
+        }
+        fs::write(dir.join("TEST.csv"), csv).unwrap();
+
+        FileSourceConfig {
+            dir,
+            timezone: Tz::Europe__Berlin,
+            ..FileSourceConfig::default()
+        }
+    }
+
+    fn midnight(year: i32, month: u32, day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn naive_dates_keep_their_local_date_ahead_of_utc() {
+        let rows = [
+            ("2024-01-02", 1.0),
+            ("2024-01-03", 2.0),
+            ("2024-01-05", 3.0),
+            ("2024-01-08", 4.0),
+            ("2024-02-01", 5.0),
+        ];
+        let config = fixture("local-date", &rows);
+        let dir = config.dir.clone();
+        let source = FileSource::new(config);
+        let token = Token::from_str("TEST").unwrap();
+        let range = DateRange {
+            start: Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+            end: Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+        };
+
+        let daily = source.fetch(&token, &range, Interval::Daily).await.unwrap();
+        let weekly = source
+            .fetch(&token, &range, Interval::Weekly)
+            .await
+            .unwrap();
+        let monthly = source
+            .fetch(&token, &range, Interval::Monthly)
+            .await
+            .unwrap();
+        fs::remove_dir_all(dir).unwrap();
+
+        // Midnight in Berlin is 23:00 UTC the day before
+        let days: Vec<NaiveDateTime> = daily.bars.keys().copied().collect();
+        assert_eq!(
+            days,
+            vec![
+                midnight(2024, 1, 2),
+                midnight(2024, 1, 3),
+                midnight(2024, 1, 5),
+                midnight(2024, 1, 8),
+                midnight(2024, 2, 1),
+            ]
+        );
+
+        // The Monday bar opens its own week, and February 1st its own month
+        let weeks: Vec<(NaiveDateTime, f64)> = weekly
+            .bars
+            .values()
+            .map(|bar| (bar.date, bar.close))
+            .collect();
+        assert_eq!(
+            weeks,
+            vec![
+                (midnight(2024, 1, 1), 3.0),
+                (midnight(2024, 1, 8), 4.0),
+                (midnight(2024, 1, 29), 5.0),
+            ]
+        );
+
+        let months: Vec<(NaiveDateTime, f64)> = monthly
+            .bars
+            .values()
+            .map(|bar| (bar.date, bar.close))
+            .collect();
+        assert_eq!(
+            months,
+            vec![(midnight(2024, 1, 1), 4.0), (midnight(2024, 2, 1), 5.0)]
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use super::{PriceSource, SourceKind};
+use crate::error::Unsupported;
+use crate::interval::Interval;
+use crate::range::DateRange;
+use crate::request::Request;
+use crate::series::PriceSeries;
+use crate::token::Token;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use reqwest::Method;
+
+/// FRED series ids of tokens whose Yahoo symbol differs.
+const SERIES_IDS: &[(&str, &str)] = &[
+    ("^GSPC", "SP500"),
+    ("^DJI", "DJIA"),
+    ("^IXIC", "NASDAQCOM"),
+    ("^VIX", "VIXCLS"),
+    ("BTC-USD", "CBBTCUSD"),
+    ("ETH-USD", "CBETHUSD"),
+    ("LTC-USD", "CBLTCUSD"),
+];
+
+/// Price source backed by the FRED graph CSV download (daily closes only).
+pub struct FredSource {
+    request: Request,
+}
+
+#[async_trait]
+impl PriceSource for FredSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::Fred
+    }
+
+    async fn fetch(
+        &self,
+        token: &Token,
+        range: &DateRange,
+        interval: Interval,
+    ) -> Result<PriceSeries, anyhow::Error> {
+        if interval == Interval::Hourly {
+            return Err(Unsupported("FRED does not serve hourly observations.".to_string()).into());
+        }
+
+        let res = self
+            .request
+            .call(
+                Method::GET,
+                "https://fred.stlouisfed.org/graph/fredgraph.csv",
+            )
+            .query("id", Self::series_id(token))
+            .query("cosd", range.start.format("%Y-%m-%d"))
+            .query("coed", range.end.format("%Y-%m-%d"))
+            .text()
+            .await?;
+
+        // Daily observations are aggregated into coarser bars by the series
+        let points = res
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let (date, value) = line.trim().split_once(',')?;
+                let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+
+                // FRED marks missing observations with "."
+                Some((
+                    date.and_hms_opt(0, 0, 0)?.and_utc(),
+                    value.parse::<f64>().ok(),
+                ))
+            })
+            .collect();
+
+        Ok(PriceSeries::from_points(token, interval, points))
+    }
+}
+
+impl FredSource {
+    /// Creates the source.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The requests to FRED.
+    ///
+    /// # Returns
+    ///
+    /// * `FredSource` - The source.
+    pub fn new(request: Request) -> FredSource {
+        FredSource { request }
+    }
+
+    /// Returns the FRED series id of a token, the symbol itself if not mapped.
+    fn series_id(token: &Token) -> &str {
+        SERIES_IDS
+            .iter()
+            .find(|(symbol, _)| *symbol == token.id())
+            .map(|(_, id)| *id)
+            .unwrap_or(token.id())
+    }
+}
@@ -0,0 +1,247 @@
+use crate::config::Config;
+use crate::interval::Interval;
+use crate::limit::RateLimiter;
+use crate::metrics::{MetricsSnapshot, ProviderMetrics};
+use crate::range::DateRange;
+use crate::request::Request;
+use crate::series::PriceSeries;
+use crate::token::Token;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub mod binance;
+pub mod coalesce;
+pub mod coingecko;
+pub mod file;
+pub mod fred;
+pub mod stooq;
+pub mod yahoo;
+
+/// Enum representing the supported market data providers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    Yahoo,
+    CoinGecko,
+    Binance,
+    Stooq,
+    Fred,
+    File,
+}
+
+impl SourceKind {
+    /// Every supported source.
+    pub const ALL: [SourceKind; 6] = [
+        SourceKind::Yahoo,
+        SourceKind::CoinGecko,
+        SourceKind::Binance,
+        SourceKind::Stooq,
+        SourceKind::Fred,
+        SourceKind::File,
+    ];
+
+    /// Returns the identifier of the source, as accepted by `from_str`.
+    pub fn id(&self) -> &str {
+        match *self {
+            SourceKind::Yahoo => "yahoo",
+            SourceKind::CoinGecko => "coingecko",
+            SourceKind::Binance => "binance",
+            SourceKind::Stooq => "stooq",
+            SourceKind::Fred => "fred",
+            SourceKind::File => "file",
+        }
+    }
+
+    /// Creates a `SourceKind` enum from a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - A string slice representing the source name.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<SourceKind>` - Representing the source if valid, `None` if invalid.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(source: &str) -> Option<SourceKind> {
+        match source.to_lowercase().as_str() {
+            "yahoo" => Some(SourceKind::Yahoo),
+            "coingecko" => Some(SourceKind::CoinGecko),
+            "binance" => Some(SourceKind::Binance),
+            "stooq" => Some(SourceKind::Stooq),
+            "fred" => Some(SourceKind::Fred),
+            "file" => Some(SourceKind::File),
+            _ => None,
+        }
+    }
+}
+
+/// A provider of historical price bars.
+#[async_trait]
+pub trait PriceSource: Send + Sync {
+    /// Returns the kind of the source.
+    fn kind(&self) -> SourceKind;
+
+    /// Fetches the bars of a token within a range.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token for which to fetch the historical data.
+    /// * `range` - The time range of historical data to fetch.
+    /// * `interval` - The bar interval of historical data to fetch.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PriceSeries, anyhow::Error>` - Result containing the historical bars keyed by bar start, or an error.
+    async fn fetch(
+        &self,
+        token: &Token,
+        range: &DateRange,
+        interval: Interval,
+    ) -> Result<PriceSeries, anyhow::Error>;
+
+    /// Prepares the source ahead of the first fetch, e.g. by opening a session.
+    async fn prepare(&self) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+}
+
+/// All configured price sources, with the one used when a request names none.
+///
+/// Every source is wrapped in a `CoalescingSource`, so concurrent fetches of
+/// the same bars share one upstream fetch.
+pub struct SourceRegistry {
+    sources: HashMap<SourceKind, Arc<dyn PriceSource>>,
+    default: Arc<dyn PriceSource>,
+    metrics: HashMap<SourceKind, Arc<ProviderMetrics>>,
+}
+
+impl SourceRegistry {
+    /// Creates the registry of every supported source.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The runtime configuration.
+    /// * `client` - The shared HTTP client of the providers.
+    ///
+    /// # Returns
+    ///
+    /// * `SourceRegistry` - The registry.
+    pub fn new(config: &Config, client: &reqwest::Client) -> SourceRegistry {
+        let metrics: HashMap<SourceKind, Arc<ProviderMetrics>> = SourceKind::ALL
+            .into_iter()
+            .map(|kind| (kind, Arc::new(ProviderMetrics::default())))
+            .collect();
+
+        let request = |kind: SourceKind| {
+            let request = Request::new(client.clone(), config.retry_policy(kind));
+
+            match config.rate_limit(kind) {
+                Some(limit) => request
+                    .with_rate_limiter(Arc::new(RateLimiter::new(limit, metrics[&kind].clone()))),
+                None => request,
+            }
+        };
+
+        let sources: Vec<Arc<dyn PriceSource>> = vec![
+            Arc::new(yahoo::YahooSource::new(
+                request(SourceKind::Yahoo),
+                config.yahoo_headers.clone(),
+            )),
+            Arc::new(coingecko::CoinGeckoSource::new(
+                request(SourceKind::CoinGecko),
+                config.coingecko_api_key.clone(),
+            )),
+            Arc::new(binance::BinanceSource::new(request(SourceKind::Binance))),
+            Arc::new(stooq::StooqSource::new(request(SourceKind::Stooq))),
+            Arc::new(fred::FredSource::new(request(SourceKind::Fred))),
+            Arc::new(file::FileSource::new(config.file_source.clone())),
+        ];
+
+        let sources: HashMap<SourceKind, Arc<dyn PriceSource>> = sources
+            .into_iter()
+            .map(|source| {
+                let kind = source.kind();
+                let coalesced: Arc<dyn PriceSource> = Arc::new(coalesce::CoalescingSource::new(
+                    source,
+                    metrics[&kind].clone(),
+                ));
+
+                (kind, coalesced)
+            })
+            .collect();
+
+        SourceRegistry {
+            default: sources[&config.price_source].clone(),
+            sources,
+            metrics,
+        }
+    }
+
+    /// Creates a registry holding a single source, e.g. a `FileSource` in library code.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The only and default source.
+    ///
+    /// # Returns
+    ///
+    /// * `SourceRegistry` - The registry.
+    pub fn from_source(source: Arc<dyn PriceSource>) -> SourceRegistry {
+        let kind = source.kind();
+        let metrics = Arc::new(ProviderMetrics::default());
+        let source: Arc<dyn PriceSource> =
+            Arc::new(coalesce::CoalescingSource::new(source, metrics.clone()));
+
+        SourceRegistry {
+            sources: HashMap::from([(kind, source.clone())]),
+            default: source,
+            metrics: HashMap::from([(kind, metrics)]),
+        }
+    }
+
+    /// Prepares every source, logging the ones that fail.
+    pub async fn prepare(&self) {
+        for source in self.sources.values() {
+            if let Err(err) = source.prepare().await {
+                warn!(
+                    "Failed to prepare the {} source: {}",
+                    source.kind().id(),
+                    err
+                );
+            }
+        }
+    }
+
+    /// Returns the fetch and request counters of every registered source.
+    pub fn metrics(&self) -> Vec<MetricsSnapshot> {
+        let mut metrics: Vec<MetricsSnapshot> = self
+            .metrics
+            .iter()
+            .filter(|(kind, _)| self.sources.contains_key(kind))
+            .map(|(kind, metrics)| metrics.snapshot(*kind))
+            .collect();
+
+        metrics.sort_by_key(|snapshot| snapshot.source.id().to_string());
+        metrics
+    }
+
+    /// Returns the default source.
+    pub fn default_source(&self) -> Arc<dyn PriceSource> {
+        self.default.clone()
+    }
+
+    /// Returns a registered source by kind.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The requested source.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Arc<dyn PriceSource>>` - The source, `None` if it is not registered.
+    pub fn get(&self, kind: SourceKind) -> Option<Arc<dyn PriceSource>> {
+        self.sources.get(&kind).cloned()
+    }
+}
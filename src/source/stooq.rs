@@ -0,0 +1,140 @@
+use super::{PriceSource, SourceKind};
+use crate::error::{UnknownSymbol, Unsupported};
+use crate::interval::Interval;
+use crate::range::DateRange;
+use crate::request::Request;
+use crate::series::{Bar, PriceSeries};
+use crate::token::Token;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use reqwest::Method;
+
+/// Stooq symbols of indices whose Yahoo symbol differs.
+const INDEX_SYMBOLS: &[(&str, &str)] = &[
+    ("^GSPC", "^spx"),
+    ("^IXIC", "^ndq"),
+    ("^NDX", "^ndx"),
+    ("^DJI", "^dji"),
+    ("^RUT", "^rut"),
+    ("^VIX", "^vix"),
+];
+
+/// Price source backed by the Stooq CSV download (daily and coarser bars).
+pub struct StooqSource {
+    request: Request,
+}
+
+#[async_trait]
+impl PriceSource for StooqSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::Stooq
+    }
+
+    async fn fetch(
+        &self,
+        token: &Token,
+        range: &DateRange,
+        interval: Interval,
+    ) -> Result<PriceSeries, anyhow::Error> {
+        let interval_id = match interval {
+            Interval::Hourly => {
+                return Err(Unsupported("Stooq does not serve hourly bars.".to_string()).into())
+            }
+            Interval::Daily => "d",
+            Interval::Weekly => "w",
+            Interval::Monthly => "m",
+        };
+
+        let res = self
+            .request
+            .call(Method::GET, "https://stooq.com/q/d/l/")
+            .query("s", Self::symbol(token))
+            .query("d1", range.start.format("%Y%m%d"))
+            .query("d2", range.end.format("%Y%m%d"))
+            .query("i", interval_id)
+            .text()
+            .await?;
+
+        if !res.starts_with("Date,") {
+            return Err(UnknownSymbol(format!(
+                "Stooq has no data for token<{}>: {}",
+                token.id(),
+                res.trim()
+            ))
+            .into());
+        }
+
+        let raw_bars = res
+            .lines()
+            .skip(1)
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Self::parse_row(line, interval))
+            .collect();
+
+        Ok(PriceSeries::from_raw_bars(token, interval, raw_bars))
+    }
+}
+
+impl StooqSource {
+    /// Creates the source.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The requests to Stooq.
+    ///
+    /// # Returns
+    ///
+    /// * `StooqSource` - The source.
+    pub fn new(request: Request) -> StooqSource {
+        StooqSource { request }
+    }
+
+    /// Returns the Stooq symbol of a token.
+    ///
+    /// Known indices are mapped explicitly, crypto pairs are joined (`BTC-USD`
+    /// is `btcusd`), plain tickers are assumed to be US listings and everything
+    /// else is passed through lowercased.
+    fn symbol(token: &Token) -> String {
+        let id = token.id();
+
+        if let Some((_, symbol)) = INDEX_SYMBOLS.iter().find(|(yahoo, _)| *yahoo == id) {
+            return symbol.to_string();
+        }
+
+        if let Some((base, quote)) = token.crypto_pair() {
+            return format!("{}{}", base, quote).to_lowercase();
+        }
+
+        if id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return format!("{}.us", id.to_lowercase());
+        }
+
+        id.to_lowercase()
+    }
+
+    /// Parses a `Date,Open,High,Low,Close[,Volume]` row.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The CSV row.
+    /// * `interval` - The bar interval, used to compute the bar start.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Bar>` - The bar, `None` if the row is malformed.
+    fn parse_row(line: &str, interval: Interval) -> Option<Bar> {
+        let columns: Vec<&str> = line.trim().split(',').collect();
+        let price = |i: usize| columns.get(i)?.parse::<f64>().ok();
+        let date = NaiveDate::parse_from_str(columns.first()?, "%Y-%m-%d").ok()?;
+
+        Some(Bar {
+            date: interval.bar_start(date.and_hms_opt(0, 0, 0)?.and_utc()),
+            open: price(1)?,
+            high: price(2)?,
+            low: price(3)?,
+            close: price(4)?,
+            volume: price(5),
+            adjclose: None,
+        })
+    }
+}
@@ -0,0 +1,232 @@
+use crate::interval::Interval;
+use crate::series::Bar;
+use chrono::DateTime;
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Errors raised while interpreting a Yahoo Finance chart payload.
+#[derive(Debug, Error)]
+pub enum YahooError {
+    /// Yahoo answered with its own `chart.error` object.
+    #[error("Yahoo Finance error <{code}>: {description}")]
+    Api { code: String, description: String },
+    /// The payload does not match the expected chart schema.
+    #[error("Unexpected Yahoo Finance chart payload: {0}")]
+    Schema(#[from] serde_json::Error),
+    /// The payload holds neither a result nor an error.
+    #[error("Yahoo Finance returned no chart data for <{0}>.")]
+    Empty(String),
+}
+
+/// Root of the v8 chart payload.
+#[derive(Debug, Deserialize)]
+pub struct ChartResponse {
+    pub chart: Chart,
+}
+
+/// The `chart` object, holding either results or an error.
+#[derive(Debug, Deserialize)]
+pub struct Chart {
+    pub result: Option<Vec<ChartResult>>,
+    pub error: Option<ChartError>,
+}
+
+/// Yahoo's own error object, e.g. `Not Found` for unknown symbols.
+#[derive(Debug, Deserialize)]
+pub struct ChartError {
+    pub code: String,
+    pub description: String,
+}
+
+/// Chart data of a single symbol.
+#[allow(unused)]
+#[derive(Debug, Deserialize)]
+pub struct ChartResult {
+    pub meta: ChartMeta,
+    #[serde(default)]
+    pub timestamp: Vec<i64>,
+    pub indicators: Indicators,
+    #[serde(default)]
+    pub events: Events,
+}
+
+/// Metadata of the symbol and exchange.
+#[allow(unused)]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartMeta {
+    pub symbol: String,
+    pub currency: Option<String>,
+    pub exchange_name: Option<String>,
+    pub full_exchange_name: Option<String>,
+    pub instrument_type: Option<String>,
+    pub first_trade_date: Option<i64>,
+    pub regular_market_time: Option<i64>,
+    pub regular_market_price: Option<f64>,
+    pub chart_previous_close: Option<f64>,
+    pub gmtoffset: Option<i64>,
+    pub timezone: Option<String>,
+    pub exchange_timezone_name: Option<String>,
+    pub price_hint: Option<i64>,
+    pub data_granularity: Option<String>,
+    pub range: Option<String>,
+    #[serde(default)]
+    pub valid_ranges: Vec<String>,
+}
+
+/// Price columns, each indexed like `ChartResult::timestamp`.
+#[derive(Debug, Deserialize)]
+pub struct Indicators {
+    #[serde(default)]
+    pub quote: Vec<Quote>,
+    #[serde(default)]
+    pub adjclose: Vec<AdjClose>,
+}
+
+/// OHLCV columns; a `None` marks a null value in the payload.
+#[derive(Debug, Default, Deserialize)]
+pub struct Quote {
+    #[serde(default)]
+    pub open: Vec<Option<f64>>,
+    #[serde(default)]
+    pub high: Vec<Option<f64>>,
+    #[serde(default)]
+    pub low: Vec<Option<f64>>,
+    #[serde(default)]
+    pub close: Vec<Option<f64>>,
+    #[serde(default)]
+    pub volume: Vec<Option<f64>>,
+}
+
+/// Split- and dividend-adjusted close column.
+#[derive(Debug, Default, Deserialize)]
+pub struct AdjClose {
+    #[serde(default)]
+    pub adjclose: Vec<Option<f64>>,
+}
+
+/// Corporate events within the requested range, keyed by timestamp.
+#[allow(unused)]
+#[derive(Debug, Default, Deserialize)]
+pub struct Events {
+    #[serde(default)]
+    pub dividends: HashMap<String, Dividend>,
+    #[serde(default)]
+    pub splits: HashMap<String, Split>,
+}
+
+/// A dividend payment.
+#[allow(unused)]
+#[derive(Debug, Deserialize)]
+pub struct Dividend {
+    pub amount: f64,
+    pub date: i64,
+}
+
+/// A stock split.
+#[allow(unused)]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Split {
+    pub date: i64,
+    pub numerator: f64,
+    pub denominator: f64,
+    pub split_ratio: String,
+}
+
+impl ChartResponse {
+    /// Parses a chart payload and extracts the result of the requested symbol.
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - The requested symbol, used in error messages.
+    /// * `value` - The raw JSON payload.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ChartResult, YahooError>` - The chart result, or Yahoo's error if there is none.
+    pub fn parse(symbol: &str, value: serde_json::Value) -> Result<ChartResult, YahooError> {
+        let response: ChartResponse = serde_json::from_value(value)?;
+
+        if let Some(error) = response.chart.error {
+            return Err(YahooError::Api {
+                code: error.code,
+                description: error.description,
+            });
+        }
+
+        response
+            .chart
+            .result
+            .and_then(|results| results.into_iter().next())
+            .ok_or_else(|| YahooError::Empty(symbol.to_string()))
+    }
+}
+
+impl ChartMeta {
+    /// Returns the timezone of the exchange, UTC if Yahoo omits it or it is unknown.
+    pub fn exchange_timezone(&self) -> Tz {
+        match &self.exchange_timezone_name {
+            Some(name) => name.parse().unwrap_or_else(|_| {
+                warn!(
+                    "Unknown exchange timezone <{}> for <{}>, dating bars in UTC.",
+                    name, self.symbol
+                );
+                Tz::UTC
+            }),
+            None => Tz::UTC,
+        }
+    }
+}
+
+impl ChartResult {
+    /// Converts the columns into bars, one per timestamp.
+    ///
+    /// Columns are zipped with the timestamps before filtering, so a null price
+    /// only drops its own bar instead of shifting later prices onto the wrong dates.
+    /// A bar with a close but a null open, high or low is kept, filled with the close.
+    /// Bars are dated in the exchange timezone, so a session opening before
+    /// midnight UTC keeps its local date.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The bar interval, used to compute each bar start.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Option<Bar>>` - The bars in payload order, `None` where the timestamp or close is null or invalid.
+    pub fn into_raw_bars(self, interval: Interval) -> Vec<Option<Bar>> {
+        let quote = self.indicators.quote.into_iter().next().unwrap_or_default();
+        let adjclose = self
+            .indicators
+            .adjclose
+            .into_iter()
+            .next()
+            .unwrap_or_default()
+            .adjclose;
+
+        let value = |column: &[Option<f64>], i: usize| column.get(i).copied().flatten();
+        let timezone = self.meta.exchange_timezone();
+
+        self.timestamp
+            .iter()
+            .enumerate()
+            .map(|(i, &timestamp)| {
+                let close = value(&quote.close, i)?;
+
+                Some(Bar {
+                    date: interval
+                        .local_bar_start(DateTime::from_timestamp(timestamp, 0)?, timezone),
+                    open: value(&quote.open, i).unwrap_or(close),
+                    high: value(&quote.high, i).unwrap_or(close),
+                    low: value(&quote.low, i).unwrap_or(close),
+                    close,
+                    volume: value(&quote.volume, i),
+                    adjclose: value(&adjclose, i),
+                })
+            })
+            .collect()
+    }
+}
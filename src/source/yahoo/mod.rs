@@ -0,0 +1,133 @@
+use super::{PriceSource, SourceKind};
+use crate::interval::Interval;
+use crate::range::DateRange;
+use crate::request::{Request, RequestError};
+use crate::series::PriceSeries;
+use crate::token::Token;
+use async_trait::async_trait;
+use chart::ChartResponse;
+use reqwest::{Method, StatusCode};
+use session::{Credentials, HeaderProfile, YahooSession};
+
+pub mod chart;
+pub mod session;
+
+/// Price source backed by the Yahoo Finance v8 chart API.
+pub struct YahooSource {
+    request: Request,
+    session: YahooSession,
+}
+
+#[async_trait]
+impl PriceSource for YahooSource {
+    fn kind(&self) -> SourceKind {
+        SourceKind::Yahoo
+    }
+
+    async fn fetch(
+        &self,
+        token: &Token,
+        range: &DateRange,
+        interval: Interval,
+    ) -> Result<PriceSeries, anyhow::Error> {
+        let credentials = self.session.credentials().await?;
+
+        let res = match self.fetch_chart(token, range, interval, &credentials).await {
+            Err(err) if err.is_unauthorized() => {
+                warn!("Yahoo rejected the session ({}), refreshing it.", err);
+                let credentials = self.session.refresh(Some(&credentials)).await?;
+                self.fetch_chart(token, range, interval, &credentials)
+                    .await?
+            }
+            res => res?,
+        };
+
+        let result = ChartResponse::parse(token.id(), res)?;
+        let raw_bars = result.into_raw_bars(interval);
+
+        Ok(PriceSeries::from_raw_bars(token, interval, raw_bars))
+    }
+
+    async fn prepare(&self) -> Result<(), anyhow::Error> {
+        self.session.credentials().await?;
+
+        Ok(())
+    }
+}
+
+impl YahooSource {
+    /// Creates the source.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The requests to Yahoo, also used to open sessions.
+    /// * `profile` - The browser headers sent with every request.
+    ///
+    /// # Returns
+    ///
+    /// * `YahooSource` - The source.
+    pub fn new(request: Request, profile: HeaderProfile) -> YahooSource {
+        YahooSource {
+            session: YahooSession::new(request.clone(), profile),
+            request,
+        }
+    }
+
+    /// Fetches the raw chart payload of a token with the given session credentials.
+    ///
+    /// Yahoo answers unknown symbols with a 404 whose body holds the chart
+    /// error, which is returned as the payload so it can be reported.
+    async fn fetch_chart(
+        &self,
+        token: &Token,
+        range: &DateRange,
+        interval: Interval,
+        credentials: &Credentials,
+    ) -> Result<serde_json::Value, RequestError> {
+        match self
+            .request
+            .call(Method::GET, Self::build_url(token))
+            .headers(self.session.headers(credentials))
+            .query("period1", range.start.timestamp())
+            .query("period2", range.end.timestamp())
+            .query("interval", interval.id())
+            // Regular sessions only, extended hours would add bars annualization does not count
+            .queries([
+                ("includePrePost", "false"),
+                ("events", "div|split|earn"),
+                ("lang", "en-US"),
+                ("region", "US"),
+            ])
+            .query("crumb", &credentials.crumb)
+            .json()
+            .await
+        {
+            Err(RequestError::Status {
+                url,
+                status: StatusCode::NOT_FOUND,
+                body,
+            }) => serde_json::from_str(&body).map_err(|_| RequestError::Status {
+                url,
+                status: StatusCode::NOT_FOUND,
+                body,
+            }),
+            res => res,
+        }
+    }
+
+    /// Builds the chart URL of a token, without query parameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token for which to build the URL.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The formatted URL.
+    fn build_url(token: &Token) -> String {
+        format!(
+            "https://query1.finance.yahoo.com/v8/finance/chart/{}",
+            urlencoding::encode(token.id())
+        )
+    }
+}
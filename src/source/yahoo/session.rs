@@ -0,0 +1,222 @@
+use crate::request::Request;
+use anyhow::anyhow;
+use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+/// Page setting the Yahoo consent cookie, answered with a 404 that still sets it.
+const COOKIE_URL: &str = "https://fc.yahoo.com";
+
+/// Endpoint returning the crumb bound to the session cookie.
+const CRUMB_URL: &str = "https://query2.finance.yahoo.com/v1/test/getcrumb";
+
+/// Browser headers sent with every Yahoo request.
+#[derive(Debug, Clone)]
+pub struct HeaderProfile {
+    headers: HeaderMap,
+}
+
+impl Default for HeaderProfile {
+    fn default() -> Self {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static("*/*"));
+        headers.insert(
+            header::ACCEPT_LANGUAGE,
+            HeaderValue::from_static("en-US,en;q=0.9"),
+        );
+        headers.insert(
+            header::ORIGIN,
+            HeaderValue::from_static("https://finance.yahoo.com"),
+        );
+        headers.insert(
+            header::REFERER,
+            HeaderValue::from_static("https://finance.yahoo.com/"),
+        );
+        headers.insert(header::USER_AGENT, HeaderValue::from_static("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/128.0.0.0 Safari/537.36"));
+
+        HeaderProfile { headers }
+    }
+}
+
+impl HeaderProfile {
+    /// Sets a header of the profile, replacing the default value if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The header name.
+    /// * `value` - The header value, an empty value removes the header.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HeaderProfile, anyhow::Error>` - The updated profile or an error if the header is malformed.
+    pub fn with_header(mut self, name: &str, value: &str) -> Result<HeaderProfile, anyhow::Error> {
+        let name = HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| anyhow!("Invalid header name: {}", name))?;
+
+        if value.is_empty() {
+            self.headers.remove(name);
+        } else {
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| anyhow!("Invalid value of header <{}>: {}", name, value))?;
+            self.headers.insert(name, value);
+        }
+
+        Ok(self)
+    }
+
+    /// Returns the headers of the profile.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+/// The session cookie and the crumb bound to it.
+#[derive(Debug)]
+pub struct Credentials {
+    pub cookie: HeaderValue,
+    pub crumb: String,
+}
+
+/// Yahoo cookie and crumb shared by all requests, obtained lazily and
+/// refreshed when Yahoo rejects them.
+pub struct YahooSession {
+    request: Request,
+    profile: HeaderProfile,
+    credentials: RwLock<Option<Arc<Credentials>>>,
+    /// Held while refreshing, so concurrent rejections trigger a single refresh.
+    refreshing: Mutex<()>,
+}
+
+impl YahooSession {
+    /// Creates a session without credentials.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The requests to Yahoo, whose client and timeout are used.
+    /// * `profile` - The browser headers sent with every request.
+    ///
+    /// # Returns
+    ///
+    /// * `YahooSession` - The session.
+    pub fn new(request: Request, profile: HeaderProfile) -> YahooSession {
+        YahooSession {
+            request,
+            profile,
+            credentials: RwLock::new(None),
+            refreshing: Mutex::new(()),
+        }
+    }
+
+    /// Returns the current credentials, obtaining them on first use.
+    pub async fn credentials(&self) -> Result<Arc<Credentials>, anyhow::Error> {
+        if let Some(credentials) = self.credentials.read().await.as_ref() {
+            return Ok(credentials.clone());
+        }
+
+        self.refresh(None).await
+    }
+
+    /// Obtains new credentials.
+    ///
+    /// Callers pass the credentials Yahoo rejected, so that when several
+    /// requests fail at once only the first one refreshes and the others reuse
+    /// its result.
+    ///
+    /// # Arguments
+    ///
+    /// * `rejected` - The credentials Yahoo rejected, `None` on first use.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Arc<Credentials>, anyhow::Error>` - The new credentials or an error.
+    pub async fn refresh(
+        &self,
+        rejected: Option<&Arc<Credentials>>,
+    ) -> Result<Arc<Credentials>, anyhow::Error> {
+        let _refreshing = self.refreshing.lock().await;
+
+        // Another request may have refreshed while this one waited for the lock
+        if let Some(current) = self.credentials.read().await.as_ref() {
+            let is_rejected = rejected.is_some_and(|rejected| Arc::ptr_eq(rejected, current));
+            if !is_rejected {
+                return Ok(current.clone());
+            }
+        }
+
+        let cookie = self.fetch_cookie().await?;
+        let crumb = self.fetch_crumb(&cookie).await?;
+        let credentials = Arc::new(Credentials { cookie, crumb });
+
+        info!("Obtained a new Yahoo session.");
+        *self.credentials.write().await = Some(credentials.clone());
+
+        Ok(credentials)
+    }
+
+    /// Builds the headers of a request authenticated with the given credentials.
+    pub fn headers(&self, credentials: &Credentials) -> HeaderMap {
+        let mut headers = self.profile.headers().clone();
+        headers.insert(header::COOKIE, credentials.cookie.clone());
+
+        headers
+    }
+
+    /// Fetches the session cookie from the `Set-Cookie` headers of the consent page.
+    async fn fetch_cookie(&self) -> Result<HeaderValue, anyhow::Error> {
+        self.request.throttle().await;
+
+        let res = self
+            .request
+            .client()
+            .get(COOKIE_URL)
+            .headers(self.profile.headers().clone())
+            .timeout(self.request.policy().timeout)
+            .send()
+            .await?;
+
+        let cookie = res
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| value.split(';').next())
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .collect::<Vec<&str>>()
+            .join("; ");
+
+        if cookie.is_empty() {
+            return Err(anyhow!(
+                "Yahoo did not set a session cookie, status {}.",
+                res.status()
+            ));
+        }
+
+        Ok(HeaderValue::from_str(&cookie)?)
+    }
+
+    /// Fetches the crumb bound to a session cookie.
+    async fn fetch_crumb(&self, cookie: &HeaderValue) -> Result<String, anyhow::Error> {
+        self.request.throttle().await;
+
+        let res = self
+            .request
+            .client()
+            .get(CRUMB_URL)
+            .headers(self.profile.headers().clone())
+            .header(header::COOKIE, cookie.clone())
+            .timeout(self.request.policy().timeout)
+            .send()
+            .await?;
+
+        let status = res.status();
+        let crumb = res.text().await?.trim().to_string();
+
+        // Rejected sessions get an HTML or JSON error page instead of a crumb
+        if !status.is_success() || crumb.is_empty() || crumb.contains(['<', '{', ' ']) {
+            return Err(anyhow!("Yahoo did not return a crumb, status {}.", status));
+        }
+
+        Ok(crumb)
+    }
+}
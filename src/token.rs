@@ -0,0 +1,125 @@
+use crate::calendar::TradingCalendar;
+use serde::{Deserialize, Serialize};
+
+/// Well-known tokens with their friendly aliases.
+///
+/// Each entry holds the aliases, the Yahoo Finance symbol, the display name and
+/// the trading calendar of the token.
+const REGISTRY: &[(&[&str], &str, &str, TradingCalendar)] = &[
+    (
+        &["bitcoin", "btc"],
+        "BTC-USD",
+        "Bitcoin",
+        TradingCalendar::Crypto,
+    ),
+    (
+        &["ethereum", "eth"],
+        "ETH-USD",
+        "Ethereum",
+        TradingCalendar::Crypto,
+    ),
+    (
+        &["solana", "sol"],
+        "SOL-USD",
+        "Solana",
+        TradingCalendar::Crypto,
+    ),
+    (
+        &["snp500", "snp", "sp500", "spx"],
+        "^GSPC",
+        "Snp500",
+        TradingCalendar::Equity,
+    ),
+];
+
+/// Quote currencies identifying `BASE-QUOTE` crypto pairs on Yahoo Finance.
+const CRYPTO_QUOTES: &[&str] = &["USD", "USDT", "USDC", "EUR", "GBP", "JPY", "BTC", "ETH"];
+
+/// Represents a tradable symbol, either a registered alias or any Yahoo-style ticker.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Token {
+    symbol: String,
+    name: String,
+    calendar: TradingCalendar,
+}
+
+impl Token {
+    /// Returns the raw (not URL-encoded) symbol of the token, e.g. `^GSPC`.
+    pub fn id(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Returns the trading calendar the token follows.
+    pub fn calendar(&self) -> TradingCalendar {
+        self.calendar
+    }
+
+    /// Creates a `Token` from a friendly alias or a raw symbol.
+    ///
+    /// Registered aliases (`btc`, `snp`, ...) resolve to their symbol, while any
+    /// other well-formed symbol (`AAPL`, `GC=F`, `DOGE-USD`, `^IXIC`) is passed through.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - A string slice representing the alias or symbol.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Token>` - Representing the token if valid, `None` if the symbol is malformed.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(token: &str) -> Option<Token> {
+        let token = token.trim();
+        let lowercase = token.to_lowercase();
+
+        let registered = REGISTRY.iter().find(|(aliases, symbol, _, _)| {
+            aliases.contains(&lowercase.as_str()) || symbol.eq_ignore_ascii_case(token)
+        });
+
+        if let Some((_, symbol, name, calendar)) = registered {
+            return Some(Token {
+                symbol: symbol.to_string(),
+                name: name.to_string(),
+                calendar: *calendar,
+            });
+        }
+
+        let is_valid = !token.is_empty()
+            && token.len() <= 32
+            && token
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '^' | '=' | '-' | '.' | '_'));
+
+        if !is_valid {
+            return None;
+        }
+
+        let symbol = token.to_uppercase();
+        let mut token = Token {
+            name: symbol.clone(),
+            symbol,
+            calendar: TradingCalendar::Equity,
+        };
+
+        // Only `BASE-QUOTE` crypto pairs trade around the clock
+        if token.crypto_pair().is_some() {
+            token.calendar = TradingCalendar::Crypto;
+        }
+
+        Some(token)
+    }
+
+    /// Returns the base and quote currencies of a `BASE-QUOTE` crypto pair.
+    pub fn crypto_pair(&self) -> Option<(&str, &str)> {
+        match self.symbol.rsplit_once('-') {
+            Some((base, quote)) if !base.is_empty() && CRYPTO_QUOTES.contains(&quote) => {
+                Some((base, quote))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the string representation of the token.
+    pub fn as_string(&self) -> &str {
+        &self.name
+    }
+}